@@ -8,21 +8,42 @@ impl AstPrinter {
         match expr {
             Expr::Binary(expr) => self.print_binary(expr),
             Expr::Logical(expr) => self.print_logical(expr),
+            Expr::Conditional(expr) => self.parenthesize("?:", &[&*expr.condition, &*expr.then_branch, &*expr.else_branch]),
             Expr::Grouping(expr) => self.print_grouping(expr),
             Expr::Literal(expr) => self.print_literal(expr),
+            Expr::Stringify(expr) => self.parenthesize("str", &[&*expr.expression]),
             Expr::Unary(expr) => self.print_unary(expr),
             Expr::Variable(expr) => self.print_variable(expr),
             Expr::Assign(expr) => self.print_assign(expr),
             Expr::Call(expr) => self.print_call(expr),
             Expr::Get(expr) => self.print_get(expr),
             Expr::Set(set) => todo!(),
+            Expr::CompoundAssign(expr) if expr.postfix => {
+                let op = &expr.operator.lexeme;
+                self.parenthesize(&format!("post{op}{op}"), &[&*expr.target])
+            }
+            Expr::CompoundAssign(expr) => self.parenthesize(&format!("{}=", expr.operator.lexeme), &[&*expr.target, &*expr.value]),
+            Expr::Lambda(expr) => {
+                let params: Vec<_> = expr.declaration.params.iter().map(|param| param.lexeme.as_str()).collect();
+                format!("(fun ({}) ...)", params.join(" "))
+            }
+            Expr::List(expr) => self.parenthesize("list", &expr.elements.iter().collect::<Vec<_>>()),
+            Expr::Map(expr) => {
+                let entries: Vec<_> = expr.entries.iter().flat_map(|(key, value)| [key, value]).collect();
+                self.parenthesize("map", &entries)
+            }
+            Expr::Index(expr) => self.parenthesize("index", &[&*expr.object, &*expr.index]),
+            Expr::SetIndex(expr) => self.parenthesize("set-index", &[&*expr.object, &*expr.index, &*expr.value]),
             Expr::This(this) => todo!(),
+            Expr::Super(expr) => format!("(super {})", expr.method.lexeme),
         }
     }
 
     fn parenthesize(&self, name: &str, exprs: &[&Expr]) -> String {
-        let parts: Vec<_> = exprs.iter().map(|expr| self.print(expr)).collect();
-        format!("({} {})", name, parts.join(" "))
+        let parts: Vec<_> = std::iter::once(name.to_string())
+            .chain(exprs.iter().map(|expr| self.print(expr)))
+            .collect();
+        format!("({})", parts.join(" "))
     }
 
     fn print_binary(&self, expr: &Binary) -> String {
@@ -66,7 +87,7 @@ impl AstPrinter {
 mod test {
     use super::*;
     use crate::{
-        expr::{Binary, Grouping, Literal, Unary},
+        expr::{Binary, CompoundAssign, Grouping, Index, List, Literal, Unary},
         token::Token,
         token_type::TokenType,
     };
@@ -81,4 +102,26 @@ mod test {
         let printer = AstPrinter {};
         assert_eq!(printer.print(&expr), "(* (- 123) (group 45.67))".to_string());
     }
+
+    #[test]
+    fn prints_lists_and_indexing() {
+        let bracket = Token::new(TokenType::LeftBracket, "[", ().into(), 1);
+        let list = List::expr(bracket.clone(), vec![Literal::expr(1_f64.into()), Literal::expr(2_f64.into())]);
+        let expr = Index::expr(list, bracket.clone(), Literal::expr(0_f64.into()));
+        let printer = AstPrinter {};
+        assert_eq!(printer.print(&expr), "(index (list 1 2) 0)".to_string());
+        assert_eq!(printer.print(&List::expr(bracket, vec![])), "(list)".to_string());
+    }
+
+    #[test]
+    fn prints_postfix_apart_from_compound_assignment() {
+        let plus = Token::new(TokenType::Plus, "+", ().into(), 1);
+        let target = Variable::expr(Token::new(TokenType::Identifier, "x", ().into(), 1));
+        let printer = AstPrinter {};
+        let postfix = CompoundAssign::expr(target.clone(), plus.clone(), Literal::expr(1_f64.into()), true);
+        let x = printer.print(&target);
+        assert_eq!(printer.print(&postfix), format!("(post++ {x})"));
+        let compound = CompoundAssign::expr(target, plus, Literal::expr(1_f64.into()), false);
+        assert_eq!(printer.print(&compound), format!("(+= {x} 1)"));
+    }
 }
@@ -3,7 +3,7 @@ use std::sync::atomic::{AtomicU32, Ordering::Relaxed};
 use macros::ExpressionType;
 use tracing::trace;
 
-use crate::token::Token;
+use crate::{stmt::Function, token::Token};
 
 #[derive(Clone, ExpressionType)]
 pub struct Binary {
@@ -25,6 +25,20 @@ pub struct Logical {
     pub right: Box<Expr>,
 }
 
+/// `condition ? then_branch : else_branch`, where only the chosen branch is evaluated
+#[derive(Clone, ExpressionType)]
+pub struct Conditional {
+    pub condition: Box<Expr>,
+    pub then_branch: Box<Expr>,
+    pub else_branch: Box<Expr>,
+}
+
+impl std::fmt::Debug for Conditional {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({:?} ? {:?} : {:?})", self.condition, self.then_branch, self.else_branch)
+    }
+}
+
 #[derive(Clone, Debug, ExpressionType)]
 pub struct Grouping {
     pub expression: Box<Expr>,
@@ -41,6 +55,14 @@ impl std::fmt::Debug for Literal {
     }
 }
 
+/// Converts the value of an expression embedded in a string to a string
+#[derive(Clone, Debug, ExpressionType)]
+pub struct Stringify {
+    /// The fragment before the `${`, to report errors on its line
+    pub fragment: Token,
+    pub expression: Box<Expr>,
+}
+
 #[derive(Clone, Debug, ExpressionType)]
 pub struct Unary {
     pub operator: Token,
@@ -55,16 +77,25 @@ pub struct Variable {
 }
 
 impl Variable {
-    pub fn expr(mut name: Token) -> Expr {
-        // Token is used as the key for locals, needs to be unique to _this_ instance of the
-        // variable being referenced to make sure scopes are correct
-        let nonce = COUNTER.fetch_add(1, Relaxed);
-        name.literal = nonce.into();
-        trace!(?name, nonce, "Creating variable");
-        Expr::Variable(Self { name })
+    pub fn new(name: Token) -> Self {
+        let name = unique(name);
+        trace!(?name, "Creating variable");
+        Self { name }
+    }
+
+    pub fn expr(name: Token) -> Expr {
+        Expr::Variable(Self::new(name))
     }
 }
 
+/// Token is used as the key for locals, needs to be unique to _this_ instance of the
+/// variable being referenced to make sure scopes are correct
+fn unique(mut token: Token) -> Token {
+    let nonce = COUNTER.fetch_add(1, Relaxed);
+    token.literal = nonce.into();
+    token
+}
+
 #[derive(Clone, ExpressionType)]
 pub struct Assign {
     pub name: Token,
@@ -77,6 +108,31 @@ impl std::fmt::Debug for Assign {
     }
 }
 
+/// `target += value` and friends, plus `++` and `--` on either side of the target. The target is a
+/// variable, property or index, and whatever it's made of is only evaluated once
+#[derive(Clone, ExpressionType)]
+pub struct CompoundAssign {
+    pub target: Box<Expr>,
+    /// The arithmetic to apply, e.g. `+` for both `+=` and `++`
+    pub operator: Token,
+    pub value: Box<Expr>,
+    /// `x++` and `x--` give back the value from before the update
+    pub postfix: bool,
+}
+
+impl std::fmt::Debug for CompoundAssign {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.postfix {
+            true => write!(
+                f,
+                "CompoundAssign(({:?} {}= {:?}) then old value)",
+                self.target, self.operator.lexeme, self.value
+            ),
+            false => write!(f, "CompoundAssign({:?} {}= {:?})", self.target, self.operator.lexeme, self.value),
+        }
+    }
+}
+
 #[derive(Clone, Debug, ExpressionType)]
 pub struct Call {
     pub callee: Box<Expr>,
@@ -109,23 +165,121 @@ pub struct Set {
 }
 
 #[derive(Clone, Debug, ExpressionType)]
+pub struct List {
+    pub bracket: Token,
+    pub elements: Vec<Expr>,
+}
+
+#[derive(Clone, Debug, ExpressionType)]
+pub struct Map {
+    pub brace: Token,
+    pub entries: Vec<(Expr, Expr)>,
+}
+
+#[derive(Clone, ExpressionType)]
+pub struct Index {
+    pub object: Box<Expr>,
+    pub bracket: Token,
+    pub index: Box<Expr>,
+}
+
+impl std::fmt::Debug for Index {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}[{:?}]", self.object, self.index)
+    }
+}
+
+#[derive(Clone, ExpressionType)]
+pub struct SetIndex {
+    pub object: Box<Expr>,
+    pub bracket: Token,
+    pub index: Box<Expr>,
+    pub value: Box<Expr>,
+}
+
+impl std::fmt::Debug for SetIndex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}[{:?}] = {:?}", self.object, self.index, self.value)
+    }
+}
+
+/// An anonymous function, `fun (a, b) { return a + b; }`
+#[derive(Clone, ExpressionType)]
+pub struct Lambda {
+    pub declaration: Function,
+}
+
+impl std::fmt::Debug for Lambda {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let params = self
+            .declaration
+            .params
+            .iter()
+            .map(|p| p.lexeme.clone())
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(
+            f,
+            "Lambda(fun ({}) {:?} [Line: {}])",
+            params, self.declaration.body, self.declaration.name.line
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct This {
     pub keyword: Token,
 }
 
+impl This {
+    pub fn expr(keyword: Token) -> Expr {
+        Expr::This(Self { keyword: unique(keyword) })
+    }
+}
+
+#[derive(Clone)]
+pub struct Super {
+    pub keyword: Token,
+    pub method: Token,
+}
+
+impl Super {
+    pub fn expr(keyword: Token, method: Token) -> Expr {
+        Expr::Super(Self {
+            keyword: unique(keyword),
+            method,
+        })
+    }
+}
+
+impl std::fmt::Debug for Super {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "super.{}", self.method.lexeme)
+    }
+}
+
 #[derive(Clone)]
 pub enum Expr {
     Binary(Binary),
     Logical(Logical),
+    Conditional(Conditional),
     Grouping(Grouping),
     Literal(Literal),
+    Stringify(Stringify),
     Unary(Unary),
     Variable(Variable),
     Assign(Assign),
+    CompoundAssign(CompoundAssign),
     Call(Call),
+    Lambda(Lambda),
     Get(Get),
     Set(Set),
+    List(List),
+    Map(Map),
+    Index(Index),
+    SetIndex(SetIndex),
     This(This),
+    Super(Super),
 }
 
 impl std::fmt::Debug for Expr {
@@ -133,15 +287,24 @@ impl std::fmt::Debug for Expr {
         match self {
             Self::Binary(expr) => write!(f, "{:?}", expr),
             Self::Logical(expr) => write!(f, "{:?}", expr),
+            Self::Conditional(expr) => write!(f, "{:?}", expr),
             Self::Grouping(expr) => write!(f, "{:?}", expr),
             Self::Literal(expr) => write!(f, "{:?}", expr),
+            Self::Stringify(expr) => write!(f, "{:?}", expr),
             Self::Unary(expr) => write!(f, "{:?}", expr),
             Self::Variable(expr) => write!(f, "{:?}", expr),
             Self::Assign(expr) => write!(f, "{:?}", expr),
+            Self::CompoundAssign(expr) => write!(f, "{:?}", expr),
             Self::Call(expr) => write!(f, "{:?}", expr),
+            Self::Lambda(expr) => write!(f, "{:?}", expr),
             Self::Get(expr) => write!(f, "{:?}", expr),
             Self::Set(expr) => write!(f, "{:?}", expr),
+            Self::List(expr) => write!(f, "{:?}", expr),
+            Self::Map(expr) => write!(f, "{:?}", expr),
+            Self::Index(expr) => write!(f, "{:?}", expr),
+            Self::SetIndex(expr) => write!(f, "{:?}", expr),
             Self::This(expr) => write!(f, "{:?}", expr),
+            Self::Super(expr) => write!(f, "{:?}", expr),
         }
     }
 }
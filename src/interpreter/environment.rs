@@ -13,6 +13,9 @@ pub(crate) type RcCell<T> = Rc<RefCell<T>>;
 pub struct Environment {
     pub values: HashMap<String, Object>,
     pub parent: Option<RcCell<Environment>>,
+    /// The line each `const` in this environment was declared on, for when something tries to
+    /// reassign it
+    consts: HashMap<String, usize>,
 }
 
 impl Environment {
@@ -32,6 +35,7 @@ impl Environment {
         Self {
             values: HashMap::new(),
             parent: None,
+            consts: HashMap::new(),
         }
     }
 
@@ -42,6 +46,7 @@ impl Environment {
         Self {
             values: HashMap::new(),
             parent: Some(enclosing),
+            consts: HashMap::new(),
         }
     }
 
@@ -52,8 +57,33 @@ impl Environment {
         trace!(current=?self.values, "done defining");
     }
 
+    pub fn define_const(&mut self, name: String, value: Object, line: usize) {
+        self.define(name.clone(), value);
+        self.consts.insert(name, line);
+    }
+
+    /// Declaring a name again replaces it, unless it's a const. The resolver catches this for locals
+    pub fn check_redeclare(&self, name: &Token) -> Result<()> {
+        match self.consts.get(&name.lexeme) {
+            Some(declared) => Err(LoxError::Runtime {
+                expected: "a name that isn't already a const".to_string(),
+                found: format!("const '{}' declared on line {declared}", name.lexeme),
+                line: Some(name.line),
+            }),
+            None => Ok(()),
+        }
+    }
+
     pub fn assign(&mut self, name: &Token, value: Object) -> Result<()> {
         trace!(?name, ?value, values = ?self.values, ">> assign()");
+        // The resolver catches this for locals, but globals are only known about at runtime
+        if let Some(declared) = self.consts.get(&name.lexeme) {
+            return Err(LoxError::Runtime {
+                expected: "a variable that can be reassigned".to_string(),
+                found: format!("const '{}' declared on line {declared}", name.lexeme),
+                line: Some(name.line),
+            });
+        }
         match self.values.entry(name.lexeme.clone()) {
             Entry::Vacant(_) => {
                 if let Some(ref outer) = self.parent {
@@ -0,0 +1,407 @@
+use std::{cell::RefCell, rc::Rc};
+
+use super::{
+    Interpreter,
+    environment::{Environment, RcCell},
+    iterator::LoxIterator,
+    targets_loop,
+};
+use crate::{
+    LoxError, Result,
+    lox_callable::{Arity, LoxCallable},
+    object::{Literal, Object},
+    stmt::{self, Stmt},
+    token::Token,
+};
+
+/// What calling a function with `yield` in it gives back. The body runs a bit at a time, up to
+/// each `yield`, so rather than recursing through statements the way `Interpreter::execute` does,
+/// a generator keeps its place in a stack of frames that it can come back to later.
+///
+/// Only statements with a `yield` somewhere inside them get frames, everything else is handed to
+/// the interpreter to run in one go.
+pub struct LoxGenerator {
+    name: String,
+    frames: Vec<Frame>,
+    /// A value that `hasNext()` ran ahead to, waiting to be handed out by `next()`
+    peeked: Option<Object>,
+}
+
+/// Where a generator is up to in one of the statements it's partway through
+enum Frame {
+    Block {
+        statements: Vec<Stmt>,
+        /// The statement to run when the generator next gets to this frame
+        next: usize,
+        environment: RcCell<Environment>,
+    },
+    While {
+        stmt: stmt::While,
+        environment: RcCell<Environment>,
+        /// The body has run at least once, so the increment is due before checking the condition
+        looped: bool,
+    },
+    ForIn {
+        stmt: stmt::ForIn,
+        iterator: LoxIterator,
+        environment: RcCell<Environment>,
+    },
+    Try {
+        stmt: stmt::Try,
+        environment: RcCell<Environment>,
+        stage: TryStage,
+        /// An error waiting for the finally block to finish before carrying on out of the `try`
+        pending: Option<LoxError>,
+    },
+}
+
+impl Frame {
+    /// The label of a loop frame, which `break` and `continue` unwind to
+    fn loop_label(&self) -> Option<&Option<Token>> {
+        match self {
+            Frame::While { stmt, .. } => Some(&stmt.label),
+            Frame::ForIn { stmt, .. } => Some(&stmt.label),
+            Frame::Block { .. } | Frame::Try { .. } => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum TryStage {
+    Body,
+    Catch,
+    Finally,
+}
+
+impl LoxGenerator {
+    /// A generator that's yet to start on `body`, with the call's arguments already in `environment`
+    pub fn new(name: &str, body: Vec<Stmt>, environment: RcCell<Environment>) -> Self {
+        Self {
+            name: name.to_string(),
+            frames: vec![Frame::Block {
+                statements: body,
+                next: 0,
+                environment,
+            }],
+            peeked: None,
+        }
+    }
+
+    /// Runs ahead to the next `yield` if need be, keeping hold of the value for `next()`
+    pub fn has_next(generator: &RcCell<LoxGenerator>, interpreter: &mut Interpreter) -> Result<bool> {
+        let mut this = running(generator)?;
+        if this.peeked.is_none() {
+            this.peeked = this.resume(interpreter)?;
+        }
+        Ok(this.peeked.is_some())
+    }
+
+    /// The next yielded value, or `None` once the body has finished
+    pub fn next(generator: &RcCell<LoxGenerator>, interpreter: &mut Interpreter) -> Result<Option<Object>> {
+        let mut this = running(generator)?;
+        match this.peeked.take() {
+            Some(value) => Ok(Some(value)),
+            None => this.resume(interpreter),
+        }
+    }
+
+    /// Generators have `next()` and `hasNext()` methods, the same as any other iterator
+    pub fn get(generator: &RcCell<LoxGenerator>, name: &Token) -> Result<Object> {
+        let method = match name.lexeme.as_str() {
+            "next" => GeneratorMethod::Next,
+            "hasNext" => GeneratorMethod::HasNext,
+            _ => {
+                return Err(LoxError::Runtime {
+                    expected: "next or hasNext".into(),
+                    found: format!("a generator method named {}", name.lexeme),
+                    line: Some(name.line),
+                });
+            }
+        };
+        Ok(Object::Callable(Rc::new(BoundGeneratorMethod {
+            generator: generator.clone(),
+            method,
+        })))
+    }
+
+    /// Carry on through the body until it yields, or runs out of frames
+    fn resume(&mut self, interpreter: &mut Interpreter) -> Result<Option<Object>> {
+        while !self.frames.is_empty() {
+            match self.step(interpreter) {
+                Ok(Some(value)) => return Ok(Some(value)),
+                Ok(None) => (),
+                Err(error) => self.unwind(interpreter, error)?,
+            }
+        }
+        Ok(None)
+    }
+
+    /// Do the next thing the innermost frame needs doing
+    fn step(&mut self, interpreter: &mut Interpreter) -> Result<Option<Object>> {
+        let Some(frame) = self.frames.last_mut() else {
+            return Ok(None);
+        };
+        match frame {
+            Frame::Block {
+                statements,
+                next,
+                environment,
+            } => {
+                let Some(statement) = statements.get(*next).cloned() else {
+                    self.frames.pop();
+                    return Ok(None);
+                };
+                *next += 1;
+                let environment = environment.clone();
+                self.start(interpreter, &statement, environment)
+            }
+            Frame::While { stmt, environment, looped } => {
+                let (stmt, environment) = (stmt.clone(), environment.clone());
+                if std::mem::replace(looped, true)
+                    && let Some(increment) = &stmt.increment
+                {
+                    interpreter.evaluate_in(increment, environment.clone())?;
+                }
+                match interpreter.evaluate_in(&stmt.condition, environment.clone())?.is_truthy() {
+                    true => self.push_block(vec![*stmt.body], environment),
+                    false => {
+                        self.frames.pop();
+                    }
+                }
+                Ok(None)
+            }
+            Frame::ForIn {
+                stmt,
+                iterator,
+                environment,
+            } => {
+                match iterator.next(interpreter, stmt.name.line)? {
+                    Some(value) => {
+                        // A fresh variable each time round, the same as outside of generators
+                        let mut scope = Environment::with_parent(environment.clone());
+                        scope.define(stmt.name.lexeme.clone(), value);
+                        let body = (*stmt.body).clone();
+                        self.push_block(vec![body], Rc::new(RefCell::new(scope)));
+                    }
+                    None => {
+                        self.frames.pop();
+                    }
+                }
+                Ok(None)
+            }
+            Frame::Try {
+                stmt,
+                environment,
+                stage,
+                pending,
+            } => {
+                // The body or catch block finished, so on to the finally block if there is one
+                if *stage != TryStage::Finally
+                    && let Some(finally) = stmt.finally.clone()
+                {
+                    *stage = TryStage::Finally;
+                    let scope = Environment::with_parent(environment.clone());
+                    self.push_block(finally, Rc::new(RefCell::new(scope)));
+                    return Ok(None);
+                }
+                let pending = pending.take();
+                self.frames.pop();
+                match pending {
+                    Some(error) => Err(error),
+                    None => Ok(None),
+                }
+            }
+        }
+    }
+
+    /// Begin running a statement. Ones without a `yield` in them run all at once, anything else
+    /// gets a frame to keep track of how far it's got
+    fn start(&mut self, interpreter: &mut Interpreter, statement: &Stmt, environment: RcCell<Environment>) -> Result<Option<Object>> {
+        if !statement.yields() {
+            interpreter.execute_block_in(std::slice::from_ref(statement), environment)?;
+            return Ok(None);
+        }
+        match statement {
+            Stmt::Yield(stmt) => {
+                let value = match &stmt.value {
+                    Some(value) => interpreter.evaluate_in(value, environment)?,
+                    None => Object::Literal(Literal::Null),
+                };
+                return Ok(Some(value));
+            }
+            Stmt::Block(block) => {
+                let scope = Environment::with_parent(environment);
+                self.push_block(block.statements.clone(), Rc::new(RefCell::new(scope)));
+            }
+            Stmt::If(stmt) => {
+                let branch = match interpreter.evaluate_in(&stmt.condition, environment.clone())?.is_truthy() {
+                    true => Some(&stmt.then_branch),
+                    false => stmt.else_branch.as_ref(),
+                };
+                if let Some(branch) = branch {
+                    self.push_block(vec![(**branch).clone()], environment);
+                }
+            }
+            Stmt::While(stmt) => self.frames.push(Frame::While {
+                stmt: stmt.clone(),
+                environment,
+                looped: false,
+            }),
+            Stmt::ForIn(stmt) => {
+                let iterable = interpreter.evaluate_in(&stmt.iterable, environment.clone())?;
+                let iterator = LoxIterator::new(interpreter, iterable, stmt.name.line)?;
+                self.frames.push(Frame::ForIn {
+                    stmt: stmt.clone(),
+                    iterator,
+                    environment,
+                });
+            }
+            Stmt::Match(stmt) => {
+                let (body, scope) = interpreter.with_environment(environment, |interpreter| interpreter.match_arm(stmt))?;
+                self.push_block(vec![body.clone()], Rc::new(RefCell::new(scope)));
+            }
+            Stmt::Try(stmt) => {
+                let scope = Environment::with_parent(environment.clone());
+                self.frames.push(Frame::Try {
+                    stmt: stmt.clone(),
+                    environment,
+                    stage: TryStage::Body,
+                    pending: None,
+                });
+                self.push_block(stmt.body.clone(), Rc::new(RefCell::new(scope)));
+            }
+            _ => {
+                return Err(LoxError::Internal {
+                    message: format!("no generator frame for {statement:?}"),
+                });
+            }
+        }
+        Ok(None)
+    }
+
+    /// Pass an error (or `return`, `break` or `continue`) out through the frames until something
+    /// deals with it. Anything that makes it all the way out finishes the generator
+    fn unwind(&mut self, interpreter: &mut Interpreter, error: LoxError) -> Result<()> {
+        let mut error = error;
+        while let Some(frame) = self.frames.last_mut() {
+            if let Some(label) = frame.loop_label() {
+                match &error {
+                    LoxError::Break { label: target } if targets_loop(target, label) => {
+                        self.frames.pop();
+                        return Ok(());
+                    }
+                    LoxError::Continue { label: target } if targets_loop(target, label) => return Ok(()),
+                    _ => (),
+                }
+            }
+            match frame {
+                Frame::Try {
+                    stmt,
+                    environment,
+                    stage,
+                    pending,
+                } => {
+                    let (stmt, environment) = (stmt.clone(), environment.clone());
+                    if *stage == TryStage::Body
+                        && let Some(catch) = stmt.catch
+                    {
+                        *stage = TryStage::Catch;
+                        match interpreter.caught_value(error) {
+                            Ok(value) => {
+                                let mut scope = Environment::with_parent(environment);
+                                scope.define(catch.name.lexeme.clone(), value);
+                                self.push_block(catch.body, Rc::new(RefCell::new(scope)));
+                                return Ok(());
+                            }
+                            Err(uncaught) => error = uncaught,
+                        }
+                    }
+                    if *stage != TryStage::Finally
+                        && let Some(finally) = stmt.finally
+                    {
+                        // An error out of the finally block replaces this one, see `execute_try_stmt`
+                        *stage = TryStage::Finally;
+                        *pending = Some(error);
+                        let scope = Environment::with_parent(environment);
+                        self.push_block(finally, Rc::new(RefCell::new(scope)));
+                        return Ok(());
+                    }
+                    self.frames.pop();
+                }
+                _ => {
+                    self.frames.pop();
+                }
+            }
+        }
+        match error {
+            LoxError::Return { .. } => Ok(()),
+            error => Err(error),
+        }
+    }
+
+    fn push_block(&mut self, statements: Vec<Stmt>, environment: RcCell<Environment>) {
+        self.frames.push(Frame::Block {
+            statements,
+            next: 0,
+            environment,
+        });
+    }
+}
+
+/// A generator can't be resumed from inside its own body
+fn running(generator: &RcCell<LoxGenerator>) -> Result<std::cell::RefMut<'_, LoxGenerator>> {
+    generator.try_borrow_mut().map_err(|_| LoxError::Runtime {
+        found: "a generator that's already running".into(),
+        expected: "a generator that's paused".into(),
+        line: None,
+    })
+}
+
+impl std::fmt::Display for LoxGenerator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<generator {}>", self.name)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum GeneratorMethod {
+    Next,
+    HasNext,
+}
+
+/// `generator.next` or `generator.hasNext`, ready to be called
+struct BoundGeneratorMethod {
+    generator: RcCell<LoxGenerator>,
+    method: GeneratorMethod,
+}
+
+impl std::fmt::Display for BoundGeneratorMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<native method {}>", self.name())
+    }
+}
+
+impl LoxCallable for BoundGeneratorMethod {
+    fn call(&self, interpreter: &mut Interpreter, _arguments: Vec<Object>) -> Result<Object> {
+        // The caller adds the line to any errors
+        match self.method {
+            GeneratorMethod::HasNext => Ok(LoxGenerator::has_next(&self.generator, interpreter)?.into()),
+            GeneratorMethod::Next => LoxGenerator::next(&self.generator, interpreter)?.ok_or_else(|| LoxError::Runtime {
+                found: "a finished generator".into(),
+                expected: "a generator with values left".into(),
+                line: None,
+            }),
+        }
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::exactly(0)
+    }
+
+    fn name(&self) -> &str {
+        match self.method {
+            GeneratorMethod::Next => "next",
+            GeneratorMethod::HasNext => "hasNext",
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use super::{Interpreter, environment::RcCell, generator::LoxGenerator};
+use crate::{
+    LoxError, Result,
+    object::{Literal, MapKey, Object},
+};
+
+/// Walks over whatever a `for (x in iterable)` loop was given
+pub enum LoxIterator {
+    /// Each character of a string, as a string of its own
+    Chars(std::vec::IntoIter<char>),
+    /// Reads the list afresh on every step, so items pushed during the loop are still visited
+    List {
+        list: RcCell<Vec<Object>>,
+        index: usize,
+    },
+    /// A map's keys, in insertion order, as they were when the loop started
+    Keys(std::vec::IntoIter<MapKey>),
+    /// An instance with `hasNext()` and `next()` methods, as returned by `iterator()`
+    Protocol(Object),
+    Generator(RcCell<LoxGenerator>),
+}
+
+impl LoxIterator {
+    pub fn new(interpreter: &mut Interpreter, iterable: Object, line: usize) -> Result<Self> {
+        let iterator = match iterable {
+            Object::Literal(Literal::String(s)) => LoxIterator::Chars(s.chars().collect::<Vec<_>>().into_iter()),
+            Object::List(list) => LoxIterator::List { list, index: 0 },
+            Object::Generator(generator) => LoxIterator::Generator(generator),
+            Object::Map(map) => LoxIterator::Keys(map.borrow().keys().cloned().collect::<Vec<_>>().into_iter()),
+            Object::Instance(_) => LoxIterator::Protocol(interpreter.call_method(&iterable, "iterator", vec![], Some(line))?),
+            _ => {
+                return Err(LoxError::Runtime {
+                    found: iterable.to_string(),
+                    expected: "a string, list, map, generator, or instance with an iterator() method".into(),
+                    line: Some(line),
+                });
+            }
+        };
+        Ok(iterator)
+    }
+
+    pub fn next(&mut self, interpreter: &mut Interpreter, line: usize) -> Result<Option<Object>> {
+        let next = match self {
+            LoxIterator::Chars(chars) => chars.next().map(|c| Object::Literal(Literal::String(c.to_string()))),
+            LoxIterator::List { list, index } => {
+                let item = list.borrow().get(*index).cloned();
+                *index += 1;
+                item
+            }
+            LoxIterator::Keys(keys) => keys.next().as_ref().map(MapKey::to_object),
+            LoxIterator::Protocol(iterator) => {
+                if !interpreter.call_method(iterator, "hasNext", vec![], Some(line))?.is_truthy() {
+                    return Ok(None);
+                }
+                Some(interpreter.call_method(iterator, "next", vec![], Some(line))?)
+            }
+            LoxIterator::Generator(generator) => LoxGenerator::next(generator, interpreter).map_err(|e| e.add_line(line))?,
+        };
+        Ok(next)
+    }
+}
@@ -1,21 +1,45 @@
 pub mod environment;
+pub mod generator;
+pub mod iterator;
 pub mod resolver;
 
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+    path::{Path, PathBuf},
+    rc::Rc,
+};
 
-use environment::{Environment, RcCell};
+use indexmap::IndexMap;
 use snafu::whatever;
 use tracing::{instrument, trace, warn};
 
+use environment::{Environment, RcCell};
+use generator::LoxGenerator;
+use iterator::LoxIterator;
+use resolver::Resolver;
+
 use super::{LoxError, Result};
 use crate::{
     expr::{self, Expr},
     lox_callable::LoxCallable as _,
     lox_class::LoxClass,
     lox_function::LoxFunction,
-    native::{assert_eq::LoxAssertEq, clock::LoxClock},
-    object::{Literal, Object},
-    stmt::{self, Stmt},
+    lox_instance::LoxInstance,
+    lox_module::LoxModule,
+    native::{
+        assert_eq::LoxAssertEq,
+        clock::LoxClock,
+        len::LoxLen,
+        list::{LoxInsert, LoxPop, LoxPush, LoxRemove, LoxSlice},
+        map::{LoxHas, LoxKeys, LoxValues},
+        string::LoxStr,
+    },
+    object::{Literal, MapKey, Object, list_index},
+    parser::Parser,
+    scanner::Scanner,
+    stmt::{self, Pattern, Stmt},
     token::Token,
     token_type::TokenType,
 };
@@ -24,29 +48,71 @@ pub struct Interpreter {
     environment: RcCell<Environment>,
     pub globals: RcCell<Environment>,
     locals: HashMap<Token, u8>,
+    /// The prelude's `Error` class, which runtime errors are converted to when caught
+    error_class: Rc<LoxClass>,
+    /// Every module imported so far, by canonical path, so that each one only runs once
+    modules: HashMap<PathBuf, Rc<LoxModule>>,
+    /// The files currently being run, the main script first. Imports are relative to the last one
+    importing: Vec<PathBuf>,
 }
 
+const PRELUDE: &str = include_str!("prelude.lox");
+
 impl Default for Interpreter {
     fn default() -> Self {
-        let bare = Rc::new(RefCell::new(Environment::new()));
-        Self {
-            environment: bare.clone(),
-            globals: bare,
-            locals: HashMap::new(),
-        }
+        Self::new()
     }
 }
 
 impl Interpreter {
     pub fn new() -> Interpreter {
-        let mut globals = Environment::new();
-        globals.define("clock".to_string(), Object::Callable(Rc::new(LoxClock {})));
-        globals.define("assert_eq".to_string(), Object::Callable(Rc::new(LoxAssertEq {})));
-        let globals = Rc::new(RefCell::new(globals));
-        Self {
+        let globals = Rc::new(RefCell::new(Self::native_globals()));
+        let mut interpreter = Self {
             environment: globals.clone(),
             globals,
             locals: HashMap::new(),
+            error_class: Rc::new(LoxClass::new("Error", None, HashMap::new(), HashMap::new())),
+            modules: HashMap::new(),
+            importing: Vec::new(),
+        };
+        interpreter.load_prelude();
+        interpreter
+    }
+
+    /// A global environment holding just the native functions
+    fn native_globals() -> Environment {
+        let mut globals = Environment::new();
+        globals.define("clock".to_string(), Object::Callable(Rc::new(LoxClock {})));
+        globals.define("assert_eq".to_string(), Object::Callable(Rc::new(LoxAssertEq {})));
+        globals.define("len".to_string(), Object::Callable(Rc::new(LoxLen {})));
+        globals.define("push".to_string(), Object::Callable(Rc::new(LoxPush {})));
+        globals.define("pop".to_string(), Object::Callable(Rc::new(LoxPop {})));
+        globals.define("insert".to_string(), Object::Callable(Rc::new(LoxInsert {})));
+        globals.define("remove".to_string(), Object::Callable(Rc::new(LoxRemove {})));
+        globals.define("slice".to_string(), Object::Callable(Rc::new(LoxSlice {})));
+        globals.define("keys".to_string(), Object::Callable(Rc::new(LoxKeys {})));
+        globals.define("values".to_string(), Object::Callable(Rc::new(LoxValues {})));
+        globals.define("has".to_string(), Object::Callable(Rc::new(LoxHas {})));
+        globals.define("str".to_string(), Object::Callable(Rc::new(LoxStr {})));
+        globals
+    }
+
+    fn load_prelude(&mut self) {
+        let tokens = Scanner::new(PRELUDE.to_string()).scan_tokens().expect("prelude should scan");
+        let statements = Parser::new(tokens).parse().expect("prelude should parse");
+        Resolver::new(self).resolve_all(&statements).expect("prelude should resolve");
+        self.interpret(statements).expect("prelude should run");
+
+        let Ok(Object::Class(error_class)) = self.globals.borrow_mut().get_at(&0, "Error") else {
+            panic!("prelude should define the Error class")
+        };
+        self.error_class = error_class;
+    }
+
+    /// Imports in the script at `path` are resolved relative to it
+    pub fn set_script(&mut self, path: &Path) {
+        if let Ok(path) = path.canonicalize() {
+            self.importing = vec![path];
         }
     }
 
@@ -63,13 +129,28 @@ impl Interpreter {
         trace!(?stmt, "Excuting statement");
         match stmt {
             Stmt::Print(stmt) => self.execute_print_stmt(stmt),
-            Stmt::Block(stmt) => self.execute_block(&stmt.statements, Environment::new()),
+            Stmt::Block(stmt) => self.execute_block(&stmt.statements, Environment::with_parent(self.environment.clone())),
             Stmt::Expression(stmt) => self.evaluate(&stmt.expression).map(|_| ()),
             Stmt::Var(stmt) => self.execute_var_stmt(stmt),
             Stmt::If(stmt) => self.execute_if_stmt(stmt),
             Stmt::While(stmt) => self.execute_while_stmt(stmt),
+            Stmt::ForIn(stmt) => self.execute_for_in_stmt(stmt),
             Stmt::Function(stmt) => self.execute_fn_stmt(stmt),
             Stmt::Return(stmt) => self.execute_return_stmt(stmt),
+            // Generators step through their own bodies, see `LoxGenerator`
+            Stmt::Yield(_) => Err(LoxError::Internal {
+                message: "yield outside of a generator".into(),
+            }),
+            Stmt::Break(stmt) => Err(LoxError::Break {
+                label: stmt.label.as_ref().map(|label| label.lexeme.clone()),
+            }),
+            Stmt::Continue(stmt) => Err(LoxError::Continue {
+                label: stmt.label.as_ref().map(|label| label.lexeme.clone()),
+            }),
+            Stmt::Import(stmt) => self.execute_import_stmt(stmt),
+            Stmt::Match(stmt) => self.execute_match_stmt(stmt),
+            Stmt::Throw(stmt) => self.execute_throw_stmt(stmt),
+            Stmt::Try(stmt) => self.execute_try_stmt(stmt),
             Stmt::Class(stmt) => self.execute_class_stmt(stmt),
         }
     }
@@ -80,37 +161,37 @@ impl Interpreter {
         match expr {
             Expr::Binary(expr) => self.eval_binary(expr),
             Expr::Logical(expr) => self.eval_logical(expr),
+            Expr::Conditional(expr) => self.eval_conditional(expr),
             Expr::Grouping(expr) => self.eval_grouping(expr),
             Expr::Literal(expr) => self.eval_literal(expr),
+            Expr::Stringify(expr) => self.eval_stringify(expr),
             Expr::Unary(expr) => self.eval_unary(expr),
             Expr::Variable(var) => self.eval_variable(var),
             Expr::Assign(assign) => self.eval_assign(assign),
+            Expr::CompoundAssign(expr) => self.eval_compound_assign(expr),
             Expr::Call(expr) => self.eval_call(expr),
+            Expr::Lambda(expr) => self.eval_lambda(expr),
             Expr::Get(expr) => self.eval_get(expr),
             Expr::Set(expr) => self.eval_set(expr),
+            Expr::List(expr) => self.eval_list(expr),
+            Expr::Map(expr) => self.eval_map(expr),
+            Expr::Index(expr) => self.eval_index(expr),
+            Expr::SetIndex(expr) => self.eval_set_index(expr),
             Expr::This(expr) => self.eval_this(expr),
+            Expr::Super(expr) => self.eval_super(expr),
         }
     }
 
-    #[instrument(skip(self))]
-    fn evaluate_literal(&mut self, expr: &Expr) -> Result<Literal> {
-        trace!(?expr, "Evaluating literal expression");
-        let as_obj = self.evaluate(expr)?;
-        if let Object::Literal(lit) = as_obj {
-            Ok(lit)
-        } else {
-            Err(LoxError::Internal {
-                message: format!("Expected a literal, found {:?}", as_obj),
-            })
-        }
+    #[instrument(skip(self), err)]
+    pub fn execute_block(&mut self, statements: &[Stmt], environment: Environment) -> Result<()> {
+        self.execute_block_in(statements, Rc::new(RefCell::new(environment)))
     }
 
-    #[instrument(skip(self), err)]
-    pub fn execute_block(&mut self, statements: &Vec<Stmt>, environment: Environment) -> Result<()> {
+    /// Like `execute_block`, for an environment that's already shared, e.g. with default arguments
+    pub fn execute_block_in(&mut self, statements: &[Stmt], environment: RcCell<Environment>) -> Result<()> {
         trace!(?environment, ?statements, ">>execute_block()");
         // TODO: consider passing environment to the visit methods instead
         //
-        let environment = Rc::new(RefCell::new(environment));
         let original_env = std::mem::replace(&mut self.environment, environment);
 
         let result = (|| {
@@ -125,10 +206,23 @@ impl Interpreter {
         result
     }
 
+    /// Evaluate an expression in some other environment, putting the current one back afterwards
+    pub fn evaluate_in(&mut self, expr: &Expr, environment: RcCell<Environment>) -> Result<Object> {
+        self.with_environment(environment, |interpreter| interpreter.evaluate(expr))
+    }
+
+    /// Run `f` with some other environment as the current one, putting the original back afterwards
+    fn with_environment<T>(&mut self, environment: RcCell<Environment>, f: impl FnOnce(&mut Self) -> T) -> T {
+        let original_env = std::mem::replace(&mut self.environment, environment);
+        let result = f(self);
+        self.environment = original_env;
+        result
+    }
+
     // TODO: shouldn't need to be mut
     fn execute_print_stmt(&mut self, stmt: &stmt::Print) -> Result<()> {
         let val = self.evaluate(&stmt.expression)?;
-        println!("{}", val);
+        println!("{}", self.stringify(&val, Some(stmt.keyword.line))?);
         Ok(())
     }
 
@@ -139,12 +233,17 @@ impl Interpreter {
         };
 
         trace!(name = stmt.name.lexeme, ?value, "Defining in env");
-        self.environment.borrow_mut().define(stmt.name.lexeme.clone(), value);
+        let mut environment = self.environment.borrow_mut();
+        environment.check_redeclare(&stmt.name)?;
+        match stmt.constant {
+            true => environment.define_const(stmt.name.lexeme.clone(), value, stmt.name.line),
+            false => environment.define(stmt.name.lexeme.clone(), value),
+        }
         Ok(())
     }
 
     fn execute_if_stmt(&mut self, stmt: &stmt::If) -> Result<()> {
-        let res = self.evaluate_literal(&stmt.condition)?;
+        let res = self.evaluate(&stmt.condition)?;
         if res.is_truthy() {
             self.execute(&stmt.then_branch)?;
         } else if let Some(ref eb) = stmt.else_branch {
@@ -155,17 +254,48 @@ impl Interpreter {
     }
 
     fn execute_while_stmt(&mut self, stmt: &stmt::While) -> Result<()> {
-        let mut res = self.evaluate_literal(&stmt.condition)?;
+        let targets_us = |target: &Option<String>| targets_loop(target, &stmt.label);
+
+        let mut res = self.evaluate(&stmt.condition)?;
         while res.is_truthy() {
-            self.execute(&stmt.body)?;
-            res = self.evaluate_literal(&stmt.condition)?;
+            match self.execute(&stmt.body) {
+                Ok(()) => (),
+                Err(LoxError::Break { label }) if targets_us(&label) => break,
+                Err(LoxError::Continue { label }) if targets_us(&label) => (),
+                Err(e) => return Err(e),
+            }
+            if let Some(ref increment) = stmt.increment {
+                self.evaluate(increment)?;
+            }
+            res = self.evaluate(&stmt.condition)?;
+        }
+
+        Ok(())
+    }
+
+    fn execute_for_in_stmt(&mut self, stmt: &stmt::ForIn) -> Result<()> {
+        let targets_us = |target: &Option<String>| targets_loop(target, &stmt.label);
+
+        let iterable = self.evaluate(&stmt.iterable)?;
+        let mut iterator = LoxIterator::new(self, iterable, stmt.name.line)?;
+        while let Some(value) = iterator.next(self, stmt.name.line)? {
+            // A new environment each time round, so closures in the body each see their own value
+            let mut environment = Environment::with_parent(self.environment.clone());
+            environment.define(stmt.name.lexeme.clone(), value);
+            match self.execute_block(std::slice::from_ref(&stmt.body), environment) {
+                Ok(()) => (),
+                Err(LoxError::Break { label }) if targets_us(&label) => break,
+                Err(LoxError::Continue { label }) if targets_us(&label) => (),
+                Err(e) => return Err(e),
+            }
         }
 
         Ok(())
     }
 
     fn execute_fn_stmt(&mut self, stmt: &stmt::Function) -> Result<()> {
-        let function = LoxFunction::new(stmt.clone(), self.environment.clone());
+        self.environment.borrow().check_redeclare(&stmt.name)?;
+        let function = LoxFunction::new(stmt.clone(), self.environment.clone(), false);
         self.environment
             .borrow_mut()
             .define(stmt.name.lexeme.clone(), Object::Callable(Rc::new(function)));
@@ -182,53 +312,351 @@ impl Interpreter {
         Err(LoxError::Return { value })
     }
 
+    fn execute_import_stmt(&mut self, stmt: &stmt::Import) -> Result<()> {
+        let module = self.load_module(stmt)?;
+        if stmt.names.is_empty() {
+            self.environment.borrow_mut().define(stmt.name.clone(), Object::Module(module));
+            return Ok(());
+        }
+        for name in stmt.names.iter() {
+            let value = module.get(name)?;
+            self.environment.borrow_mut().define(name.lexeme.clone(), value);
+        }
+        Ok(())
+    }
+
+    /// Run the file an import refers to in its own global environment, or reuse it if it already ran
+    fn load_module(&mut self, stmt: &stmt::Import) -> Result<Rc<LoxModule>> {
+        let import_error = |message: String| LoxError::Import {
+            path: stmt.path.clone(),
+            message,
+            line: stmt.keyword.line,
+        };
+
+        // From the REPL, imports are relative to the working directory
+        let base = self
+            .importing
+            .last()
+            .and_then(|importer| importer.parent())
+            .unwrap_or(Path::new(""));
+        let path = base.join(&stmt.path).canonicalize().map_err(|e| import_error(e.to_string()))?;
+
+        if let Some(start) = self.importing.iter().position(|file| *file == path) {
+            let chain = self.importing[start..]
+                .iter()
+                .chain([&path])
+                .map(|file| file.display().to_string())
+                .collect::<Vec<_>>();
+            return Err(import_error(format!("circular import {}", chain.join(" -> "))));
+        }
+        if let Some(module) = self.modules.get(&path) {
+            return Ok(module.clone());
+        }
+
+        let source = std::fs::read_to_string(&path).map_err(|e| import_error(e.to_string()))?;
+        let tokens = Scanner::new(source)
+            .scan_tokens()
+            .map_err(|_| import_error("module has syntax errors".into()))?;
+        let statements = Parser::new(tokens)
+            .parse()
+            .map_err(|_| import_error("module has syntax errors".into()))?;
+        Resolver::new(self)
+            .resolve_all(&statements)
+            .map_err(|_| import_error("module failed static analysis".into()))?;
+
+        let mut globals = Self::native_globals();
+        globals.define("Error".into(), Object::Class(self.error_class.clone()));
+        let globals = Rc::new(RefCell::new(globals));
+
+        self.importing.push(path.clone());
+        let enclosing = std::mem::replace(&mut self.environment, globals.clone());
+        let result = self.interpret(statements);
+        self.environment = enclosing;
+        self.importing.pop();
+        result?;
+
+        let module = Rc::new(LoxModule::new(&stmt.name, globals));
+        self.modules.insert(path, module.clone());
+        Ok(module)
+    }
+
+    fn execute_match_stmt(&mut self, stmt: &stmt::Match) -> Result<()> {
+        let (body, environment) = self.match_arm(stmt)?;
+        self.execute_block(std::slice::from_ref(body), environment)
+    }
+
+    /// The body of the first arm that matches, and an environment holding what its pattern bound
+    fn match_arm<'a>(&mut self, stmt: &'a stmt::Match) -> Result<(&'a Stmt, Environment)> {
+        let value = self.evaluate(&stmt.value)?;
+        for arm in stmt.arms.iter() {
+            let mut bindings = Vec::new();
+            if self.match_pattern(&arm.pattern, &value, &mut bindings)? {
+                let mut environment = Environment::with_parent(self.environment.clone());
+                for (name, value) in bindings {
+                    environment.define(name, value);
+                }
+                return Ok((&arm.body, environment));
+            }
+        }
+        Err(LoxError::Runtime {
+            found: value.to_string(),
+            expected: "a match arm for the value".into(),
+            line: Some(stmt.keyword.line),
+        })
+    }
+
+    /// Whether `value` fits `pattern`, collecting the names it binds along the way
+    fn match_pattern(&mut self, pattern: &Pattern, value: &Object, bindings: &mut Vec<(String, Object)>) -> Result<bool> {
+        let matched = match pattern {
+            Pattern::Wildcard => true,
+            Pattern::Literal(literal) => matches!(value, Object::Literal(v) if v == literal),
+            // Only numbers are ordered, anything else falls outside the range
+            Pattern::Range { start, end, inclusive } => match value {
+                Object::Literal(v) => start <= v && if *inclusive { v <= end } else { v < end },
+                _ => false,
+            },
+            Pattern::Binding(name) => {
+                bindings.push((name.lexeme.clone(), value.clone()));
+                true
+            }
+            Pattern::Alternatives(alternatives) => {
+                for alternative in alternatives.iter() {
+                    if self.match_pattern(alternative, value, bindings)? {
+                        return Ok(true);
+                    }
+                }
+                false
+            }
+            Pattern::Class { name, fields } => {
+                let Object::Class(class) = self.lookup_variable(name)? else {
+                    return Err(LoxError::Runtime {
+                        found: name.name.lexeme.clone(),
+                        expected: "a class in a class pattern".into(),
+                        line: Some(name.name.line),
+                    });
+                };
+                let Object::Instance(instance) = value else {
+                    return Ok(false);
+                };
+                if !instance.borrow().class.is_subclass_of(&class) {
+                    return Ok(false);
+                }
+                for (field, pattern) in fields.iter() {
+                    let Some(field) = instance.borrow().field(&field.lexeme).cloned() else {
+                        return Ok(false);
+                    };
+                    if !self.match_pattern(pattern, &field, bindings)? {
+                        return Ok(false);
+                    }
+                }
+                true
+            }
+        };
+        Ok(matched)
+    }
+
+    fn execute_throw_stmt(&mut self, stmt: &stmt::Throw) -> Result<()> {
+        let value = self.evaluate(&stmt.value)?;
+        // An `Error` thrown by the script gets the line it was thrown from, unless it already has one
+        if let Object::Instance(ref instance) = value {
+            let mut instance = instance.borrow_mut();
+            if instance.class.is_subclass_of(&self.error_class)
+                && matches!(instance.field("line"), None | Some(Object::Literal(Literal::Null)))
+            {
+                instance.set("line", (stmt.keyword.line as i64).into());
+            }
+        }
+        Err(LoxError::Throw {
+            value,
+            line: stmt.keyword.line,
+        })
+    }
+
+    fn execute_try_stmt(&mut self, stmt: &stmt::Try) -> Result<()> {
+        let mut result = self.execute_block(&stmt.body, Environment::with_parent(self.environment.clone()));
+
+        if let Some(ref catch) = stmt.catch
+            && let Err(error) = result
+        {
+            result = match self.caught_value(error) {
+                Ok(value) => {
+                    let mut environment = Environment::with_parent(self.environment.clone());
+                    environment.define(catch.name.lexeme.clone(), value);
+                    self.execute_block(&catch.body, environment)
+                }
+                Err(error) => Err(error),
+            };
+        }
+
+        if let Some(ref finally) = stmt.finally {
+            // An error, `return` or `break` out of the finally block replaces whatever was in flight
+            self.execute_block(finally, Environment::with_parent(self.environment.clone()))?;
+        }
+        result
+    }
+
+    /// The value a `catch` block receives for an error, or the error itself if it can't be caught.
+    /// Thrown values are passed through as they are, runtime errors become instances of `Error`
+    fn caught_value(&mut self, error: LoxError) -> Result<Object> {
+        match error {
+            LoxError::Throw { value, .. } => Ok(value),
+            LoxError::Runtime { .. } | LoxError::Import { .. } => {
+                let (message, line) = match error {
+                    LoxError::Runtime { found, expected, line } => (format!("expected {expected}, found {found}"), line),
+                    LoxError::Import { path, message, line } => (format!("could not import '{path}': {message}"), Some(line)),
+                    _ => unreachable!(),
+                };
+                let message = Literal::String(message);
+                let error_class = self.error_class.clone();
+                let error = LoxClass::instantiate(&error_class, self, vec![Object::Literal(message)])?;
+                if let Object::Instance(ref instance) = error {
+                    let line = line.map_or(Object::Literal(Literal::Null), |line| (line as i64).into());
+                    instance.borrow_mut().set("line", line);
+                }
+                Ok(error)
+            }
+            _ => Err(error),
+        }
+    }
+
     fn execute_class_stmt(&mut self, stmt: &stmt::Class) -> Result<()> {
+        let superclass = match &stmt.superclass {
+            Some(var) => match self.eval_variable(var)? {
+                Object::Class(class) => Some(class),
+                other => {
+                    return Err(LoxError::Runtime {
+                        found: other.to_string(),
+                        expected: "superclass to be a class".to_string(),
+                        line: Some(var.name.line),
+                    });
+                }
+            },
+            None => None,
+        };
+
+        self.environment.borrow().check_redeclare(&stmt.name)?;
         self.environment
             .borrow_mut()
             .define(stmt.name.lexeme.clone(), Object::Literal(Literal::Null));
 
+        // Methods close over an extra environment holding `super` for a subclass, and the class
+        // itself so that private members are looked up on the class whose code is running
+        let enclosing = self.environment.clone();
+        let mut environment = Environment::with_parent(enclosing.clone());
+        if let Some(ref superclass) = superclass {
+            environment.define("super".into(), Object::Class(superclass.clone()));
+        }
+        self.environment = Rc::new(RefCell::new(environment));
+
         let mut methods = HashMap::new();
         for method in stmt.methods.iter() {
-            let function = LoxFunction::new(method.clone(), self.environment.clone());
+            let is_initializer = method.name.lexeme == "init";
+            let function = LoxFunction::new(method.clone(), self.environment.clone(), is_initializer);
             methods.insert(method.name.lexeme.clone(), function);
         }
 
-        let class = LoxClass::new(&stmt.name.lexeme, methods);
-        self.environment.borrow_mut().assign(&stmt.name, Object::Callable(Rc::new(class)))?;
+        let mut class_methods = HashMap::new();
+        for method in stmt.class_methods.iter() {
+            let function = LoxFunction::new(method.clone(), self.environment.clone(), false);
+            class_methods.insert(method.name.lexeme.clone(), function);
+        }
+
+        let class = Rc::new(LoxClass::new(&stmt.name.lexeme, superclass, methods, class_methods));
+        self.environment.borrow_mut().define("#class".into(), Object::Class(class.clone()));
+        self.environment = enclosing;
+        self.environment.borrow_mut().assign(&stmt.name, Object::Class(class))?;
         Ok(())
     }
 
     fn eval_binary(&mut self, expr: &expr::Binary) -> Result<Object> {
         let left = self.evaluate(&expr.left)?;
         let right = self.evaluate(&expr.right)?;
+        self.binary_op(left, &expr.operator, right)
+    }
 
-        let obj = match expr.operator.typ {
+    /// Apply a binary operator to values that have already been evaluated
+    fn binary_op(&mut self, left: Object, operator: &Token, right: Object) -> Result<Object> {
+        // `"total: " + money` converts the instance to a string, unless it overloads `+` itself
+        if operator.typ == TokenType::Plus {
+            match (&left, &right) {
+                (Object::Literal(Literal::String(first)), Object::Instance(_)) if !has_method(&right, "__radd__") => {
+                    return Ok(Object::Literal(Literal::String(
+                        first.clone() + &self.stringify(&right, Some(operator.line))?,
+                    )));
+                }
+                (Object::Instance(_), Object::Literal(Literal::String(second))) if !has_method(&left, "__add__") => {
+                    return Ok(Object::Literal(Literal::String(
+                        self.stringify(&left, Some(operator.line))? + second,
+                    )));
+                }
+                _ => (),
+            }
+        }
+        if let Some(result) = self.overloaded_binary_op(&left, operator, &right)? {
+            return Ok(result);
+        }
+        let line = operator.line;
+        let obj = match operator.typ {
             TokenType::Greater => (left > right).into(),
             TokenType::GreaterEqual => (left >= right).into(),
             TokenType::Less => (left < right).into(),
             TokenType::LessEqual => (left <= right).into(),
-            TokenType::Minus => (left - right).map_err(|e| e.add_line(expr.operator.line))?,
-            TokenType::Plus => (left + right).map_err(|e| e.add_line(expr.operator.line))?,
-            TokenType::Slash => (left / right).map_err(|e| e.add_line(expr.operator.line))?,
-            TokenType::Star => (left * right).map_err(|e| e.add_line(expr.operator.line))?,
-            TokenType::EqualEqual => (left == right).into(),
-            TokenType::BangEqual => (left != right).into(),
+            TokenType::Minus => (left - right).map_err(|e| e.add_line(line))?,
+            TokenType::Plus => (left + right).map_err(|e| e.add_line(line))?,
+            TokenType::Slash => (left / right).map_err(|e| e.add_line(line))?,
+            TokenType::Star => (left * right).map_err(|e| e.add_line(line))?,
+            TokenType::TildeSlash => left.integer_div(right).map_err(|e| e.add_line(line))?,
+            TokenType::Percent => (left % right).map_err(|e| e.add_line(line))?,
+            TokenType::StarStar => left.pow(right).map_err(|e| e.add_line(line))?,
+            TokenType::Ampersand => (left & right).map_err(|e| e.add_line(line))?,
+            TokenType::Pipe => (left | right).map_err(|e| e.add_line(line))?,
+            TokenType::Caret => (left ^ right).map_err(|e| e.add_line(line))?,
+            TokenType::LessLess => (left << right).map_err(|e| e.add_line(line))?,
+            TokenType::GreaterGreater => (left >> right).map_err(|e| e.add_line(line))?,
+            TokenType::EqualEqual => self.values_equal(&left, &right, Some(line))?.into(),
+            TokenType::BangEqual => (!self.values_equal(&left, &right, Some(line))?).into(),
             _ => Object::Literal(Literal::Null),
         };
 
         Ok(obj)
     }
 
+    /// Operators on instances call their special methods, like `__add__` for `+`. When the left operand
+    /// doesn't have one, the right operand's reflected method is used instead, like `__radd__` for
+    /// `2 + vector`
+    fn overloaded_binary_op(&mut self, left: &Object, operator: &Token, right: &Object) -> Result<Option<Object>> {
+        let Some((method, reflected)) = operator_methods(&operator.typ) else {
+            return Ok(None);
+        };
+        let (instance, method, argument) = match (left, right) {
+            _ if has_method(left, method) => (left, method, right),
+            (_, Object::Instance(_)) if has_method(right, reflected) || !matches!(left, Object::Instance(_)) => (right, reflected, left),
+            // Let the left operand complain about the missing method
+            (Object::Instance(_), _) => (left, method, right),
+            _ => return Ok(None),
+        };
+        self.call_method(instance, method, vec![argument.clone()], Some(operator.line))
+            .map(Some)
+    }
+
     fn eval_logical(&mut self, expr: &expr::Logical) -> Result<Object> {
-        let left = self.evaluate_literal(&expr.left)?;
+        let left = self.evaluate(&expr.left)?;
 
         let truthy_left = left.is_truthy();
         match (&expr.operator.typ, truthy_left) {
-            (&TokenType::Or, true) | (&TokenType::And, false) => Ok(Object::Literal(left)),
+            (&TokenType::Or, true) | (&TokenType::And, false) => Ok(left),
             _ => self.evaluate(&expr.right),
         }
     }
 
+    fn eval_conditional(&mut self, expr: &expr::Conditional) -> Result<Object> {
+        if self.evaluate(&expr.condition)?.is_truthy() {
+            self.evaluate(&expr.then_branch)
+        } else {
+            self.evaluate(&expr.else_branch)
+        }
+    }
+
     fn eval_grouping(&mut self, expr: &expr::Grouping) -> Result<Object> {
         self.evaluate(&expr.expression)
     }
@@ -238,18 +666,96 @@ impl Interpreter {
         Ok(Object::Literal(expr.value.clone()))
     }
 
+    fn eval_stringify(&mut self, expr: &expr::Stringify) -> Result<Object> {
+        let value = self.evaluate(&expr.expression)?;
+        Ok(Object::Literal(Literal::String(self.stringify(&value, Some(expr.fragment.line))?)))
+    }
+
+    /// How a value reads as a string, for `print`, interpolation, concatenation and `str()`. Instances
+    /// of classes with a `toString()` method use it, anything else has a default
+    pub fn stringify(&mut self, object: &Object, line: Option<usize>) -> Result<String> {
+        self.stringify_visiting(object, line, &mut Vec::new())
+    }
+
+    /// `visited` holds the collections already being written further up, which are shown as `[...]`
+    /// or `{...}` rather than written out again
+    fn stringify_visiting(&mut self, object: &Object, line: Option<usize>, visited: &mut Vec<*const ()>) -> Result<String> {
+        if let Some(ptr) = object.collection_ptr() {
+            if visited.contains(&ptr) {
+                return Ok(match object {
+                    Object::List(_) => "[...]".into(),
+                    _ => "{...}".into(),
+                });
+            }
+            visited.push(ptr);
+        }
+        let string = match object {
+            Object::Instance(_) if has_method(object, "toString") => match self.call_method(object, "toString", vec![], line)? {
+                Object::Literal(Literal::String(string)) => string,
+                other => {
+                    return Err(LoxError::Runtime {
+                        found: other.to_string(),
+                        expected: "toString() to return a string".into(),
+                        line,
+                    });
+                }
+            },
+            // Collections are written out here too, so that anything inside them gets converted the same way
+            Object::List(list) => {
+                let items = list.borrow().clone();
+                let items = items
+                    .iter()
+                    .map(|item| self.stringify_nested(item, line, visited))
+                    .collect::<Result<Vec<_>>>()?;
+                format!("[{}]", items.join(", "))
+            }
+            Object::Map(map) => {
+                let entries = map.borrow().clone();
+                let entries = entries
+                    .into_iter()
+                    .map(|(key, value)| {
+                        let key = self.stringify_nested(&key.to_object(), line, visited)?;
+                        Ok(format!("{key}: {}", self.stringify_nested(&value, line, visited)?))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                format!("{{{}}}", entries.join(", "))
+            }
+            other => other.to_string(),
+        };
+        if object.collection_ptr().is_some() {
+            visited.pop();
+        }
+        Ok(string)
+    }
+
+    /// Strings inside collections are quoted, the same as `Display` does for them
+    fn stringify_nested(&mut self, object: &Object, line: Option<usize>, visited: &mut Vec<*const ()>) -> Result<String> {
+        match object {
+            Object::Literal(Literal::String(s)) => Ok(format!("\"{s}\"")),
+            other => self.stringify_visiting(other, line, visited),
+        }
+    }
+
     fn eval_unary(&mut self, expr: &expr::Unary) -> Result<Object> {
-        let right = self.evaluate_literal(&expr.right)?;
-        let obj = match expr.operator.typ {
-            TokenType::Minus => {
-                let n = right.into_number().map_err(|e| e.add_line(expr.operator.line))?;
-                Object::from(-n)
+        let right = self.evaluate(&expr.right)?;
+        if let Object::Instance(_) = right {
+            let method = match expr.operator.typ {
+                TokenType::Minus => Some("__neg__"),
+                TokenType::Tilde => Some("__invert__"),
+                _ => None,
+            };
+            if let Some(method) = method {
+                return self.call_method(&right, method, vec![], Some(expr.operator.line));
             }
+        }
+        let obj = match expr.operator.typ {
+            TokenType::Minus => (-right).map_err(|e| e.add_line(expr.operator.line))?,
             TokenType::Bang => (!right.is_truthy()).into(),
+            TokenType::Tilde => (!right).map_err(|e| e.add_line(expr.operator.line))?,
             _ => {
                 let token = expr.operator.clone(); // TODO: clone
                 Err(LoxError::Runtime {
-                    expected: "'!' or '-' unary operator".to_string(),
+                    expected: "'!', '-' or '~' unary operator".to_string(),
                     found: token.to_string(),
                     line: Some(token.line),
                 })
@@ -264,17 +770,68 @@ impl Interpreter {
     }
 
     fn eval_assign(&mut self, assign: &expr::Assign) -> Result<Object> {
-        let name = &assign.name;
         let value = self.evaluate(&assign.value)?;
-        let distance = self.locals.get(&assign.name);
+        self.assign_variable(&assign.name, value.clone())?;
+        Ok(value)
+    }
+
+    fn assign_variable(&mut self, name: &Token, value: Object) -> Result<()> {
+        let distance = self.locals.get(name);
         if let Some(distance) = distance {
             trace!(distance, ?value, ?name, "Assigning to local");
-            self.environment.borrow_mut().assign_at(distance, &name.lexeme, value.clone())?;
+            self.environment.borrow_mut().assign_at(distance, &name.lexeme, value)
         } else {
             trace!(?value, ?name, "Assigning to global");
-            self.environment.borrow_mut().assign(name, value.clone())?;
+            self.environment.borrow_mut().assign(name, value)
         }
-        Ok(value)
+    }
+
+    fn eval_compound_assign(&mut self, expr: &expr::CompoundAssign) -> Result<Object> {
+        // The parts of the target are evaluated once, up front, then used for both reading and writing
+        let (old, new) = match &*expr.target {
+            Expr::Variable(var) => {
+                let old = self.lookup_variable(var)?;
+                let value = self.evaluate(&expr.value)?;
+                let new = self.binary_op(old.clone(), &expr.operator, value)?;
+                self.assign_variable(&var.name, new.clone())?;
+                (old, new)
+            }
+            Expr::Get(get) => {
+                let Object::Instance(instance) = self.evaluate(&get.object)? else {
+                    return Err(LoxError::Runtime {
+                        found: "a value without fields".into(),
+                        expected: "an instance to update a property of".into(),
+                        line: Some(get.name.line),
+                    });
+                };
+                let owner = self.private_owner(&get.object, &get.name)?;
+                let old = match &owner {
+                    Some(owner) => LoxInstance::get_private(&instance, owner, &get.name, self)?,
+                    None => LoxInstance::get(&instance, &get.name, self)?,
+                };
+                let value = self.evaluate(&expr.value)?;
+                let new = self.binary_op(old.clone(), &expr.operator, value)?;
+                match &owner {
+                    Some(owner) => instance.borrow_mut().set_private(owner, &get.name.lexeme, new.clone()),
+                    None => instance.borrow_mut().set(&get.name.lexeme, new.clone()),
+                }
+                (old, new)
+            }
+            Expr::Index(index) => {
+                let object = self.evaluate(&index.object)?;
+                let key = self.evaluate(&index.index)?;
+                let old = self
+                    .index_get(&object, &key, index.bracket.line)
+                    .map_err(|e| e.add_line(index.bracket.line))?;
+                let value = self.evaluate(&expr.value)?;
+                let new = self.binary_op(old.clone(), &expr.operator, value)?;
+                self.index_set(&object, &key, new.clone(), index.bracket.line)
+                    .map_err(|e| e.add_line(index.bracket.line))?;
+                (old, new)
+            }
+            _ => whatever!("Invalid compound assignment target {:?}", expr.target),
+        };
+        Ok(if expr.postfix { old } else { new })
     }
 
     fn eval_call(&mut self, expr: &expr::Call) -> Result<Object> {
@@ -284,7 +841,14 @@ impl Interpreter {
             arguments.push(self.evaluate(argument)?);
         }
         let function = callee;
-        if arguments.len() as u8 != function.arity() {
+        if !matches!(function, Object::Callable(_) | Object::Class(_)) {
+            return Err(LoxError::Runtime {
+                found: function.to_string(),
+                expected: "a function or class to call".into(),
+                line: Some(expr.paren.line),
+            });
+        }
+        if !function.arity().accepts(arguments.len()) {
             return Err(LoxError::Runtime {
                 line: Some(expr.paren.line),
                 expected: format!("{} arguments", function.arity()),
@@ -294,6 +858,43 @@ impl Interpreter {
         function.call(self, arguments).map_err(|e| e.add_line(expr.paren.line))
     }
 
+    /// Calls the method `name` on an instance with the given arguments, for protocols like iteration
+    pub fn call_method(&mut self, object: &Object, name: &str, arguments: Vec<Object>, line: Option<usize>) -> Result<Object> {
+        let Object::Instance(instance) = object else {
+            return Err(LoxError::Runtime {
+                found: object.to_string(),
+                expected: format!("an instance with a {name}() method"),
+                line,
+            });
+        };
+        let name = Token::new(TokenType::Identifier, name, Literal::Null, line.unwrap_or_default());
+        let method = LoxInstance::get(instance, &name, self)?;
+        if !matches!(method, Object::Callable(_)) {
+            return Err(LoxError::Runtime {
+                found: method.to_string(),
+                expected: format!("{}() to be a method", name.lexeme),
+                line,
+            });
+        }
+        if !method.arity().accepts(arguments.len()) {
+            return Err(LoxError::Runtime {
+                line,
+                expected: format!("{}() to take {} arguments", name.lexeme, method.arity()),
+                found: format!("{} arguments", arguments.len()),
+            });
+        }
+        // Without a line, the caller's own call expression fills it in
+        method.call(self, arguments).map_err(|e| match line {
+            Some(line) => e.add_line(line),
+            None => e,
+        })
+    }
+
+    fn eval_lambda(&mut self, expr: &expr::Lambda) -> Result<Object> {
+        let function = LoxFunction::new(expr.declaration.clone(), self.environment.clone(), false);
+        Ok(Object::Callable(Rc::new(function)))
+    }
+
     fn resolve(&mut self, token: &Token, i: u8) {
         if self.locals.contains_key(token) {
             panic!("Tried to insert {token:?} at depth {i} over {:?}", self.locals.get(token).unwrap());
@@ -316,34 +917,40 @@ impl Interpreter {
 
     fn eval_get(&mut self, expr: &expr::Get) -> Result<Object> {
         let object = self.evaluate(&expr.object)?;
-        if let Object::Instance(instance) = object {
-            return instance.get(&expr.name);
+        if let Some(owner) = self.private_owner(&expr.object, &expr.name)? {
+            // `this` is an instance in a method, or the class itself in a class method
+            return match object {
+                Object::Instance(instance) => LoxInstance::get_private(&instance, &owner, &expr.name, self),
+                Object::Class(class) => LoxClass::get_private(&class, &owner, &expr.name, self),
+                _ => whatever!("'this' was not bound correctly"),
+            };
+        }
+        match object {
+            Object::Instance(instance) => return LoxInstance::get(&instance, &expr.name, self),
+            Object::Class(class) => return LoxClass::get(&class, &expr.name, self),
+            Object::Module(module) => return module.get(&expr.name),
+            Object::Generator(generator) => return LoxGenerator::get(&generator, &expr.name),
+            _ => (),
         }
 
-        Err(LoxError::Internal {
-            message: "Only instances have properties.".to_string(),
+        Err(LoxError::Runtime {
+            found: object.to_string(),
+            expected: "an instance to have properties".into(),
+            line: Some(expr.name.line),
         })
     }
 
     fn eval_set(&mut self, expr: &expr::Set) -> Result<Object> {
         let object = self.evaluate(&expr.object)?;
 
-        if let Object::Instance(mut object) = object {
+        if let Object::Instance(object) = object {
+            let owner = self.private_owner(&expr.object, &expr.name)?;
             let value = self.evaluate(&expr.value)?;
-            object.set(expr.name.clone(), value.clone());
-            trace!(?expr,?object,  ?value, env = ?self.environment.borrow().values, "Object after setting");
-
-            // Update env with the mutated object
-            //let Expr::Variable(ref var_expr) = *expr.object else {
-            //    whatever!("wrong kind of expr: {:?}", *expr.object)
-            //};
-            let t = match *expr.object {
-                Expr::Variable(ref var_expr) => var_expr.name.clone(),
-                Expr::This(ref t) => t.keyword.clone(),
-                _ => whatever!("Wrong kind of expr"),
-            };
-            trace!(name = ?t, "Updating mutated obj");
-            self.environment.borrow_mut().assign(&t, Object::Instance(object))?;
+            match owner {
+                Some(owner) => object.borrow_mut().set_private(&owner, &expr.name.lexeme, value.clone()),
+                None => object.borrow_mut().set(&expr.name.lexeme, value.clone()),
+            }
+            trace!(?expr, ?object, ?value, "Object after setting");
 
             Ok(value)
         } else {
@@ -355,6 +962,174 @@ impl Interpreter {
         }
     }
 
+    fn eval_list(&mut self, expr: &expr::List) -> Result<Object> {
+        let mut elements = Vec::with_capacity(expr.elements.len());
+        for element in expr.elements.iter() {
+            elements.push(self.evaluate(element)?);
+        }
+        Ok(Object::List(Rc::new(RefCell::new(elements))))
+    }
+
+    fn eval_map(&mut self, expr: &expr::Map) -> Result<Object> {
+        let map = Rc::new(RefCell::new(IndexMap::with_capacity(expr.entries.len())));
+        for (key, value) in expr.entries.iter() {
+            let key = self.evaluate(key)?;
+            let key = self.map_key(&map, &key, Some(expr.brace.line))?;
+            let value = self.evaluate(value)?;
+            map.borrow_mut().insert(key, value);
+        }
+        Ok(Object::Map(map))
+    }
+
+    fn eval_index(&mut self, expr: &expr::Index) -> Result<Object> {
+        let object = self.evaluate(&expr.object)?;
+        let index = self.evaluate(&expr.index)?;
+        self.index_get(&object, &index, expr.bracket.line)
+            .map_err(|e| e.add_line(expr.bracket.line))
+    }
+
+    fn eval_set_index(&mut self, expr: &expr::SetIndex) -> Result<Object> {
+        let object = self.evaluate(&expr.object)?;
+        let index = self.evaluate(&expr.index)?;
+        let value = self.evaluate(&expr.value)?;
+        self.index_set(&object, &index, value.clone(), expr.bracket.line)
+            .map_err(|e| e.add_line(expr.bracket.line))?;
+        Ok(value)
+    }
+
+    /// `object[index]` on a list or map
+    fn index_get(&mut self, object: &Object, index: &Object, line: usize) -> Result<Object> {
+        match object {
+            Object::List(list) => {
+                let list = list.borrow();
+                list_index(index, list.len(), false).map(|position| list[position].clone())
+            }
+            Object::Map(map) => {
+                let key = self.map_key(map, index, Some(line))?;
+                map.borrow().get(&key).cloned().ok_or(LoxError::Runtime {
+                    found: format!("no entry for {key}"),
+                    expected: "a key present in the map".into(),
+                    line: None,
+                })
+            }
+            _ => Err(LoxError::Runtime {
+                found: object.to_string(),
+                expected: "a list or map to index into".into(),
+                line: None,
+            }),
+        }
+    }
+
+    /// `object[index] = value` on a list or map
+    fn index_set(&mut self, object: &Object, index: &Object, value: Object, line: usize) -> Result<()> {
+        match object {
+            Object::List(list) => {
+                let mut list = list.borrow_mut();
+                list_index(index, list.len(), false).map(|position| list[position] = value)
+            }
+            Object::Map(map) => {
+                let key = self.map_key(map, index, Some(line))?;
+                map.borrow_mut().insert(key, value);
+                Ok(())
+            }
+            _ => Err(LoxError::Runtime {
+                found: object.to_string(),
+                expected: "a list or map to index into".into(),
+                line: None,
+            }),
+        }
+    }
+
+    /// The key to look `key` up by in `map`. Literals are used as they are. Instances are keyed by
+    /// identity, unless their class defines `hash()` and `equals()`, in which case an existing key
+    /// that's equal to them is used instead
+    pub fn map_key(&mut self, map: &RcCell<IndexMap<MapKey, Object>>, key: &Object, line: Option<usize>) -> Result<MapKey> {
+        let instance = match key {
+            Object::Literal(literal) => return Ok(MapKey::Literal(literal.clone())),
+            Object::Instance(instance) => instance,
+            other => {
+                return Err(LoxError::Runtime {
+                    found: other.to_string(),
+                    expected: "a string, number, boolean, nil or instance as a map key".to_string(),
+                    line,
+                });
+            }
+        };
+
+        // Equal keys need equal hashes, so a class with its own equals() needs its own hash() too
+        let custom_equality = has_method(key, "equals");
+        let hash = if has_method(key, "hash") {
+            match self.call_method(key, "hash", vec![], line)? {
+                Object::Literal(literal) => hash_of(&literal),
+                other => {
+                    return Err(LoxError::Runtime {
+                        found: other.to_string(),
+                        expected: "hash() to return a string, number, boolean or nil".into(),
+                        line,
+                    });
+                }
+            }
+        } else if custom_equality {
+            return Err(LoxError::Runtime {
+                found: format!("{key} with equals() but no hash()"),
+                expected: "a class with both equals() and hash() as a map key".into(),
+                line,
+            });
+        } else {
+            hash_of(&(Rc::as_ptr(instance) as usize))
+        };
+
+        let key = MapKey::Instance {
+            instance: instance.clone(),
+            hash,
+        };
+        if custom_equality {
+            // Collected first, since equals() might want to look at the map itself
+            let candidates = map
+                .borrow()
+                .keys()
+                .filter(|existing| matches!(existing, MapKey::Instance { hash: h, .. } if *h == hash) && **existing != key)
+                .cloned()
+                .collect::<Vec<_>>();
+            for candidate in candidates {
+                if self.values_equal(&key.to_object(), &candidate.to_object(), line)? {
+                    return Ok(candidate);
+                }
+            }
+        }
+        Ok(key)
+    }
+
+    /// `==`, which uses an `equals()` method when either side has one, and identity for instances otherwise
+    pub fn values_equal(&mut self, left: &Object, right: &Object, line: Option<usize>) -> Result<bool> {
+        if has_method(left, "equals") {
+            return Ok(self.call_method(left, "equals", vec![right.clone()], line)?.is_truthy());
+        }
+        if has_method(right, "equals") {
+            return Ok(self.call_method(right, "equals", vec![left.clone()], line)?.is_truthy());
+        }
+        Ok(left == right)
+    }
+
+    /// The class whose methods are accessing `name`, if it's a private name. The resolver has
+    /// already made sure those are only used on `this`
+    fn private_owner(&mut self, object: &Expr, name: &Token) -> Result<Option<Rc<LoxClass>>> {
+        if name.typ != TokenType::PrivateIdentifier {
+            return Ok(None);
+        }
+        let Expr::This(this) = object else {
+            whatever!("Private member used on something other than 'this': {:?}", name)
+        };
+        let Some(distance) = self.locals.get(&this.keyword).copied() else {
+            whatever!("'this' was not resolved: {:?}", this.keyword)
+        };
+        // The class is bound in the environment just outside the one holding `this`
+        match self.environment.borrow_mut().get_at(&(distance + 1), "#class")? {
+            Object::Class(class) => Ok(Some(class)),
+            _ => whatever!("The class was not bound correctly for {:?}", name),
+        }
+    }
+
     fn eval_this(&mut self, expr: &expr::This) -> Result<Object> {
         trace!(?expr, ">>eval_this()");
         let var = expr::Variable {
@@ -364,4 +1139,80 @@ impl Interpreter {
         trace!(?var, "<<eval_this()");
         var
     }
+
+    fn eval_super(&mut self, expr: &expr::Super) -> Result<Object> {
+        let Some(distance) = self.locals.get(&expr.keyword).copied() else {
+            whatever!("'super' was not resolved: {:?}", expr.keyword)
+        };
+        let superclass = self.environment.borrow_mut().get_at(&distance, "super")?;
+        // `this` is always bound in the environment just inside the one holding `super`
+        let object = self.environment.borrow_mut().get_at(&(distance - 1), "this")?;
+
+        let Object::Class(superclass) = superclass else {
+            whatever!("'super' was not bound correctly")
+        };
+        // In a class method `this` is the class, so `super` looks up the superclass's class methods
+        let method = match object {
+            Object::Instance(_) => superclass.find_method(&expr.method.lexeme),
+            Object::Class(_) => superclass.find_class_method(&expr.method.lexeme),
+            _ => whatever!("'this' was not bound correctly"),
+        };
+
+        match method {
+            Some(method) => method.bind_property(object, self),
+            None => Err(LoxError::Runtime {
+                expected: format!("method named {} on superclass {}", expr.method.lexeme, superclass),
+                found: "no such method".into(),
+                line: Some(expr.method.line),
+            }),
+        }
+    }
+}
+
+fn hash_of(value: &impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Whether the object is an instance whose class has (or inherits) the given method
+fn has_method(object: &Object, name: &str) -> bool {
+    match object {
+        Object::Instance(instance) => instance.borrow().class.find_method(name).is_some(),
+        _ => false,
+    }
+}
+
+/// The special method an instance can define to overload a binary operator, and the reflected
+/// one to use when the instance is on the right. Comparisons reflect to their mirror image
+fn operator_methods(operator: &TokenType) -> Option<(&'static str, &'static str)> {
+    let methods = match operator {
+        TokenType::Plus => ("__add__", "__radd__"),
+        TokenType::Minus => ("__sub__", "__rsub__"),
+        TokenType::Star => ("__mul__", "__rmul__"),
+        TokenType::Slash => ("__div__", "__rdiv__"),
+        TokenType::TildeSlash => ("__floordiv__", "__rfloordiv__"),
+        TokenType::Percent => ("__mod__", "__rmod__"),
+        TokenType::StarStar => ("__pow__", "__rpow__"),
+        TokenType::Ampersand => ("__and__", "__rand__"),
+        TokenType::Pipe => ("__or__", "__ror__"),
+        TokenType::Caret => ("__xor__", "__rxor__"),
+        TokenType::LessLess => ("__lshift__", "__rlshift__"),
+        TokenType::GreaterGreater => ("__rshift__", "__rrshift__"),
+        TokenType::Less => ("__lt__", "__gt__"),
+        TokenType::LessEqual => ("__le__", "__ge__"),
+        TokenType::Greater => ("__gt__", "__lt__"),
+        TokenType::GreaterEqual => ("__ge__", "__le__"),
+        _ => return None,
+    };
+    Some(methods)
+}
+
+/// An unlabelled `break`/`continue` targets the innermost loop, a labelled one only its own loop
+fn targets_loop(target: &Option<String>, label: &Option<Token>) -> bool {
+    match (target, label) {
+        (None, _) => true,
+        (Some(target), Some(label)) => *target == label.lexeme,
+        (Some(_), None) => false,
+    }
 }
@@ -7,14 +7,36 @@ use super::Interpreter;
 use crate::{
     LoxError, Result,
     expr::Expr,
-    stmt::{self, Stmt},
+    stmt::{self, Pattern, Stmt},
     token::Token,
+    token_type::TokenType,
 };
 
 pub struct Resolver<'a> {
     interpreter: &'a mut Interpreter,
-    scopes: Vec<HashMap<String, bool>>,
+    scopes: Vec<HashMap<String, Binding>>,
     curr_fn: FunctionType,
+    curr_class: ClassType,
+    /// Labels of the loops enclosing the current statement, innermost last
+    loops: Vec<Option<String>>,
+}
+
+/// What the resolver knows about a name declared in a local scope
+#[derive(Clone, Copy, Default)]
+struct Binding {
+    /// False between declaring a variable and finishing its initializer
+    defined: bool,
+    /// The line a `const` was declared on, since those can't be assigned to
+    const_line: Option<usize>,
+}
+
+impl Binding {
+    fn defined() -> Self {
+        Self {
+            defined: true,
+            const_line: None,
+        }
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -22,6 +44,14 @@ enum FunctionType {
     None,
     Function,
     Method,
+    Initializer,
+}
+
+#[derive(Clone, Copy)]
+enum ClassType {
+    None,
+    Class,
+    Subclass,
 }
 
 impl<'a> Resolver<'a> {
@@ -30,6 +60,8 @@ impl<'a> Resolver<'a> {
             interpreter,
             scopes: vec![],
             curr_fn: FunctionType::None,
+            curr_class: ClassType::None,
+            loops: vec![],
         }
     }
 
@@ -56,10 +88,10 @@ impl Resolver<'_> {
         match expr {
             Expr::Variable(var) => {
                 trace!("Expr::Variable {}", &var.name);
-                if let Some(peeked) = self.scopes.last() {
-                    if peeked.get(&var.name.lexeme) == Option::from(&false) {
-                        whatever!("Cannot read a local variable in its own initializer.");
-                    }
+                if let Some(peeked) = self.scopes.last()
+                    && peeked.get(&var.name.lexeme).is_some_and(|binding| !binding.defined)
+                {
+                    whatever!("Cannot read a local variable in its own initializer.");
                 }
 
                 self.resolve_local(&var.name)?;
@@ -67,8 +99,16 @@ impl Resolver<'_> {
             Expr::Assign(assign) => {
                 trace!("Expr::Assign {}", &assign.name);
                 self.resolve_expr(&assign.value)?;
+                self.check_assignable(&assign.name)?;
                 self.resolve_local(&assign.name)?;
             }
+            Expr::CompoundAssign(assign) => {
+                self.resolve_expr(&assign.value)?;
+                if let Expr::Variable(var) = &*assign.target {
+                    self.check_assignable(&var.name)?;
+                }
+                self.resolve_expr(&assign.target)?;
+            }
             Expr::Binary(binary) => {
                 trace!(?expr, "Expr::Binary");
                 self.resolve_expr(&binary.left)?;
@@ -80,23 +120,65 @@ impl Resolver<'_> {
                     self.resolve_expr(arg)?;
                 }
             }
+            Expr::Lambda(lambda) => self.resolve_func(&lambda.declaration, FunctionType::Function)?,
             Expr::Get(get) => {
+                self.check_private(&get.object, &get.name)?;
                 self.resolve_expr(&get.object)?;
             }
+            Expr::Conditional(conditional) => {
+                self.resolve_expr(&conditional.condition)?;
+                self.resolve_expr(&conditional.then_branch)?;
+                self.resolve_expr(&conditional.else_branch)?;
+            }
             Expr::Grouping(group) => {
                 self.resolve_expr(&group.expression)?;
             }
             Expr::Literal(_) => (),
+            Expr::Stringify(stringify) => self.resolve_expr(&stringify.expression)?,
             Expr::Logical(logic) => {
                 self.resolve_expr(&logic.left)?;
                 self.resolve_expr(&logic.right)?;
             }
             Expr::Unary(unary) => self.resolve_expr(&unary.right)?,
             Expr::Set(set) => {
+                self.check_private(&set.object, &set.name)?;
+                self.resolve_expr(&set.value)?;
+                self.resolve_expr(&set.object)?;
+            }
+            Expr::List(list) => {
+                for element in list.elements.iter() {
+                    self.resolve_expr(element)?;
+                }
+            }
+            Expr::Map(map) => {
+                for (key, value) in map.entries.iter() {
+                    self.resolve_expr(key)?;
+                    self.resolve_expr(value)?;
+                }
+            }
+            Expr::Index(index) => {
+                self.resolve_expr(&index.object)?;
+                self.resolve_expr(&index.index)?;
+            }
+            Expr::SetIndex(set) => {
                 self.resolve_expr(&set.value)?;
                 self.resolve_expr(&set.object)?;
+                self.resolve_expr(&set.index)?;
+            }
+            Expr::This(this) => {
+                if let ClassType::None = self.curr_class {
+                    whatever!("Cannot use 'this' outside of a class. {:?}", this.keyword)
+                }
+                self.resolve_local(&this.keyword)?
+            }
+            Expr::Super(sup) => {
+                match self.curr_class {
+                    ClassType::None => whatever!("Cannot use 'super' outside of a class. {:?}", sup.keyword),
+                    ClassType::Class => whatever!("Cannot use 'super' in a class with no superclass. {:?}", sup.keyword),
+                    ClassType::Subclass => (),
+                }
+                self.resolve_local(&sup.keyword)?
             }
-            Expr::This(this) => self.resolve_local(&this.keyword)?,
         }
         trace!(?expr, "Exited expression");
         Ok(())
@@ -115,6 +197,11 @@ impl Resolver<'_> {
                     self.resolve_expr(initializer)?;
                 }
                 self.define(&var.name.lexeme)?;
+                if var.constant
+                    && let Some(binding) = self.scopes.last_mut().and_then(|scope| scope.get_mut(&var.name.lexeme))
+                {
+                    binding.const_line = Some(var.name.line);
+                }
             }
             Stmt::Function(func) => {
                 self.declare(&func.name.lexeme)?;
@@ -138,54 +225,224 @@ impl Resolver<'_> {
                     whatever!("Cannot return from top-level code. {:?}", stmt.keyword)
                 }
                 if let Some(val) = &stmt.value {
+                    if let FunctionType::Initializer = self.curr_fn {
+                        whatever!("Cannot return a value from an initializer. {:?}", stmt.keyword)
+                    }
                     self.resolve_expr(val)?;
                 }
             }
+            Stmt::Yield(stmt) => {
+                match self.curr_fn {
+                    FunctionType::None => whatever!("Cannot yield from top-level code. {:?}", stmt.keyword),
+                    FunctionType::Initializer => whatever!("Cannot yield from an initializer. {:?}", stmt.keyword),
+                    FunctionType::Function | FunctionType::Method => (),
+                }
+                if let Some(value) = &stmt.value {
+                    self.resolve_expr(value)?;
+                }
+            }
             Stmt::While(stmt) => {
                 self.resolve_expr(&stmt.condition)?;
-                self.resolve_stmt(&stmt.body)?;
+                self.loops.push(stmt.label.as_ref().map(|label| label.lexeme.clone()));
+                let body = self.resolve_stmt(&stmt.body);
+                self.loops.pop();
+                body?;
+                if let Some(increment) = &stmt.increment {
+                    self.resolve_expr(increment)?;
+                }
             }
-            Stmt::Block(block) => {
+            Stmt::ForIn(stmt) => {
+                self.resolve_expr(&stmt.iterable)?;
+                self.loops.push(stmt.label.as_ref().map(|label| label.lexeme.clone()));
+                // The loop variable lives in a scope of its own around the body
                 self.begin_scope();
-                self.resolve_all(&block.statements)?;
+                let body = self
+                    .declare(&stmt.name.lexeme)
+                    .and_then(|_| self.define(&stmt.name.lexeme))
+                    .and_then(|_| self.resolve_stmt(&stmt.body));
                 self.end_scope()?;
+                self.loops.pop();
+                body?;
+            }
+            Stmt::Break(stmt) => self.resolve_loop_jump("break", &stmt.keyword, &stmt.label)?,
+            Stmt::Continue(stmt) => self.resolve_loop_jump("continue", &stmt.keyword, &stmt.label)?,
+            Stmt::Block(block) => self.resolve_block(&block.statements)?,
+            Stmt::Import(stmt) => {
+                if stmt.names.is_empty() {
+                    self.declare(&stmt.name)?;
+                    self.define(&stmt.name)?;
+                }
+                for name in stmt.names.iter() {
+                    self.declare(&name.lexeme)?;
+                    self.define(&name.lexeme)?;
+                }
             }
-            Stmt::Class(stmt) => {
-                self.declare(&stmt.name.lexeme)?;
-                self.define(&stmt.name.lexeme)?;
+            Stmt::Match(stmt) => {
+                self.resolve_expr(&stmt.value)?;
+                // Each arm gets its own scope holding whatever its pattern binds
+                for arm in stmt.arms.iter() {
+                    self.begin_scope();
+                    let result = self.resolve_pattern(&arm.pattern).and_then(|_| self.resolve_stmt(&arm.body));
+                    self.end_scope()?;
+                    result?;
+                }
+            }
+            Stmt::Throw(stmt) => self.resolve_expr(&stmt.value)?,
+            Stmt::Try(stmt) => {
+                self.resolve_block(&stmt.body)?;
+                if let Some(catch) = &stmt.catch {
+                    // The error variable lives in the same scope as the catch body
+                    self.begin_scope();
+                    let result = self
+                        .declare(&catch.name.lexeme)
+                        .and_then(|_| self.define(&catch.name.lexeme))
+                        .and_then(|_| self.resolve_all(&catch.body));
+                    self.end_scope()?;
+                    result?;
+                }
+                if let Some(finally) = &stmt.finally {
+                    self.resolve_block(finally)?;
+                }
+            }
+            Stmt::Class(stmt) => self.resolve_class(stmt)?,
+        }
+        trace!(?statement, "Finished resolving statement");
+        Ok(())
+    }
 
-                self.begin_scope();
+    fn resolve_block(&mut self, statements: &Vec<Stmt>) -> Result<()> {
+        self.begin_scope();
+        let result = self.resolve_all(statements);
+        self.end_scope()?;
+        result
+    }
+
+    fn resolve_pattern(&mut self, pattern: &Pattern) -> Result<()> {
+        match pattern {
+            Pattern::Wildcard | Pattern::Literal(_) | Pattern::Range { .. } => (),
+            Pattern::Binding(name) => {
+                self.declare(&name.lexeme)?;
+                self.define(&name.lexeme)?;
+            }
+            Pattern::Alternatives(alternatives) => {
+                for alternative in alternatives.iter() {
+                    if let Some(name) = first_binding(alternative) {
+                        whatever!("Cannot bind names in alternative patterns. {:?}", name)
+                    }
+                    self.resolve_pattern(alternative)?;
+                }
+            }
+            Pattern::Class { name, fields } => {
+                self.resolve_local(&name.name)?;
+                for (_, field) in fields.iter() {
+                    self.resolve_pattern(field)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn resolve_class(&mut self, stmt: &stmt::Class) -> Result<()> {
+        let enclosing_class = self.curr_class;
+        let depth = self.scopes.len();
+        self.curr_class = ClassType::Class;
+
+        let result = (|| {
+            self.declare(&stmt.name.lexeme)?;
+            self.define(&stmt.name.lexeme)?;
+
+            if let Some(superclass) = &stmt.superclass {
+                if superclass.name.lexeme == stmt.name.lexeme {
+                    whatever!("A class cannot inherit from itself. {:?}", superclass.name)
+                }
+                self.curr_class = ClassType::Subclass;
+                self.resolve_local(&superclass.name)?;
+            }
+
+            // The class's own scope holds `super`, if it has one, and the class for private lookups
+            self.begin_scope();
+            if stmt.superclass.is_some() {
                 self.scopes
                     .last_mut()
                     .ok_or(LoxError::Internal {
                         message: "Didn't have a last scope".into(),
                     })?
-                    .insert("this".into(), true);
+                    .insert("super".into(), Binding::defined());
+            }
 
-                for method in stmt.methods.iter() {
-                    let declaration = FunctionType::Method;
-                    self.resolve_func(method, declaration)?;
-                }
+            self.begin_scope();
+            self.scopes
+                .last_mut()
+                .ok_or(LoxError::Internal {
+                    message: "Didn't have a last scope".into(),
+                })?
+                .insert("this".into(), Binding::defined());
 
-                self.end_scope()?;
+            for method in stmt.methods.iter() {
+                let declaration = if method.name.lexeme == "init" {
+                    FunctionType::Initializer
+                } else {
+                    FunctionType::Method
+                };
+                self.resolve_func(method, declaration)?;
+            }
+            // `this` in a class method is the class itself
+            for method in stmt.class_methods.iter() {
+                self.resolve_func(method, FunctionType::Method)?;
             }
+
+            self.end_scope()?;
+            self.end_scope()?;
+            Ok(())
+        })();
+
+        // Don't leak class state into the rest of the analysis if the body had errors
+        self.curr_class = enclosing_class;
+        self.scopes.truncate(depth);
+        result
+    }
+
+    fn resolve_loop_jump(&self, kind: &str, keyword: &Token, label: &Option<Token>) -> Result<()> {
+        if self.loops.is_empty() {
+            whatever!("Cannot use '{kind}' outside of a loop. {:?}", keyword)
+        }
+        if let Some(label) = label
+            && !self.loops.iter().flatten().any(|l| *l == label.lexeme)
+        {
+            whatever!("No enclosing loop labelled '{}' for '{kind}'. {:?}", label.lexeme, keyword)
         }
-        trace!(?statement, "Finished resolving statement");
         Ok(())
     }
 
     fn resolve_func(&mut self, func: &stmt::Function, typ: FunctionType) -> Result<()> {
         let enclosing_fn = self.curr_fn;
+        // Loops don't reach across function boundaries
+        let enclosing_loops = std::mem::take(&mut self.loops);
+        let depth = self.scopes.len();
         self.curr_fn = typ;
-        self.begin_scope();
-        for param in func.params.iter() {
-            self.declare(&param.lexeme)?;
-            self.define(&param.lexeme)?;
-        }
-        self.resolve_all(&func.body)?;
-        self.end_scope()?;
+
+        let result = (|| {
+            self.begin_scope();
+            for (param, default) in func.params.iter().zip(func.defaults.iter()) {
+                // Resolved before the parameter exists, so it can only see the ones before it
+                if let Some(default) = default {
+                    self.resolve_expr(default)?;
+                }
+                self.declare(&param.lexeme)?;
+                self.define(&param.lexeme)?;
+            }
+            if let Some(rest) = &func.rest {
+                self.declare(&rest.lexeme)?;
+                self.define(&rest.lexeme)?;
+            }
+            self.resolve_all(&func.body)?;
+            self.end_scope()
+        })();
+
         self.curr_fn = enclosing_fn;
-        Ok(())
+        self.loops = enclosing_loops;
+        self.scopes.truncate(depth);
+        result
     }
 }
 
@@ -214,10 +471,16 @@ impl Resolver<'_> {
         }
 
         if let Some(peeked) = self.scopes.last_mut() {
+            if let Some(Binding {
+                const_line: Some(line), ..
+            }) = peeked.get(name)
+            {
+                whatever!("Cannot redeclare const '{name}' declared on line {line}.");
+            }
             if peeked.contains_key(name) {
                 whatever!("'{name}' is already defined in this scope");
             }
-            peeked.insert(name.to_string(), false);
+            peeked.insert(name.to_string(), Binding::default());
         } else {
             whatever!("Should have a scope by 'declare'")
         }
@@ -233,7 +496,7 @@ impl Resolver<'_> {
         }
 
         if let Some(peeked) = self.scopes.last_mut() {
-            peeked.insert(name.to_string(), true);
+            peeked.entry(name.to_string()).or_default().defined = true;
         } else {
             whatever!("Didn't have initial scope in define")
         }
@@ -241,6 +504,30 @@ impl Resolver<'_> {
         Ok(())
     }
 
+    /// Assigning to a local `const` is an error. Global ones are checked at runtime instead
+    fn check_assignable(&self, name: &Token) -> Result<()> {
+        let binding = self.scopes.iter().rev().find_map(|scope| scope.get(&name.lexeme));
+        if let Some(Binding {
+            const_line: Some(line), ..
+        }) = binding
+        {
+            whatever!("Cannot assign to const '{}' declared on line {line}. {:?}", name.lexeme, name)
+        }
+        Ok(())
+    }
+
+    /// `#name` can only be used on `this`, so it's only ever reached from the class's own methods
+    fn check_private(&self, object: &Expr, name: &Token) -> Result<()> {
+        if name.typ == TokenType::PrivateIdentifier && (matches!(self.curr_class, ClassType::None) || !matches!(object, Expr::This(_))) {
+            whatever!(
+                "Private member '{}' can only be accessed through 'this' inside its class. {:?}",
+                name.lexeme,
+                name
+            )
+        }
+        Ok(())
+    }
+
     fn resolve_local(&mut self, token: &Token) -> Result<()> {
         trace!(?token, len = self.scopes.len(), "Resolving local");
         let top = self.scopes.len();
@@ -256,3 +543,13 @@ impl Resolver<'_> {
         Ok(())
     }
 }
+
+/// The first name a pattern would bind, if any
+fn first_binding(pattern: &Pattern) -> Option<&Token> {
+    match pattern {
+        Pattern::Binding(name) => Some(name),
+        Pattern::Alternatives(alternatives) => alternatives.iter().find_map(first_binding),
+        Pattern::Class { fields, .. } => fields.iter().find_map(|(_, field)| first_binding(field)),
+        Pattern::Wildcard | Pattern::Literal(_) | Pattern::Range { .. } => None,
+    }
+}
@@ -2,6 +2,64 @@ use crate::{LoxError, interpreter::Interpreter, object::Object};
 
 pub trait LoxCallable: std::fmt::Display {
     fn call(&self, interpreter: &mut Interpreter, arguments: Vec<Object>) -> Result<Object, LoxError>;
-    fn arity(&self) -> u8;
+    fn arity(&self) -> Arity;
     fn name(&self) -> &str;
 }
+
+/// How many arguments a callable accepts
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Arity {
+    pub min: u8,
+    /// `None` when a rest parameter takes any number of extra arguments
+    pub max: Option<u8>,
+}
+
+impl Arity {
+    pub fn exactly(count: u8) -> Self {
+        Self {
+            min: count,
+            max: Some(count),
+        }
+    }
+
+    pub fn accepts(&self, count: usize) -> bool {
+        count >= self.min as usize && self.max.is_none_or(|max| count <= max as usize)
+    }
+}
+
+impl std::fmt::Display for Arity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.max {
+            Some(max) if max == self.min => write!(f, "{max}"),
+            Some(max) => write!(f, "{} to {max}", self.min),
+            None => write!(f, "at least {}", self.min),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn arity_accepts_its_range() {
+        let exact = Arity::exactly(2);
+        assert!(!exact.accepts(1));
+        assert!(exact.accepts(2));
+        assert!(!exact.accepts(3));
+        assert_eq!(exact.to_string(), "2");
+
+        let defaults = Arity { min: 1, max: Some(3) };
+        assert!(!defaults.accepts(0));
+        assert!(defaults.accepts(3));
+        assert!(!defaults.accepts(4));
+        assert_eq!(defaults.to_string(), "1 to 3");
+
+        let rest = Arity { min: 1, max: None };
+        assert!(!rest.accepts(0));
+        assert!(rest.accepts(200));
+        assert_eq!(rest.to_string(), "at least 1");
+    }
+}
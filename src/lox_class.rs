@@ -1,42 +1,104 @@
-use std::collections::HashMap;
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
 use crate::{
-    LoxError, interpreter::Interpreter, lox_callable::LoxCallable, lox_function::LoxFunction, lox_instance::LoxInstance, object::Object,
+    LoxError,
+    interpreter::Interpreter,
+    lox_callable::{Arity, LoxCallable},
+    lox_function::LoxFunction,
+    lox_instance::LoxInstance,
+    object::Object,
+    token::Token,
 };
 
 #[derive(Clone, Debug)]
 pub struct LoxClass {
     pub name: String,
+    pub superclass: Option<Rc<LoxClass>>,
     pub methods: HashMap<String, LoxFunction>,
+    pub class_methods: HashMap<String, LoxFunction>,
 }
 
 impl LoxClass {
-    pub fn new<T>(name: T, methods: HashMap<String, LoxFunction>) -> Self
+    pub fn new<T>(
+        name: T,
+        superclass: Option<Rc<LoxClass>>,
+        methods: HashMap<String, LoxFunction>,
+        class_methods: HashMap<String, LoxFunction>,
+    ) -> Self
     where
         T: Into<String>,
     {
         Self {
             name: name.into(),
+            superclass,
             methods,
+            class_methods,
         }
     }
 
+    /// Look up a method on this class, walking up the superclass chain if needed
     pub fn find_method(&self, name: &str) -> Option<LoxFunction> {
-        self.methods.get(name).cloned()
+        match self.methods.get(name) {
+            Some(method) => Some(method.clone()),
+            None => self.superclass.as_ref().and_then(|superclass| superclass.find_method(name)),
+        }
+    }
+
+    /// Look up a class method, which subclasses inherit just like instance methods
+    pub fn find_class_method(&self, name: &str) -> Option<LoxFunction> {
+        match self.class_methods.get(name) {
+            Some(method) => Some(method.clone()),
+            None => self.superclass.as_ref().and_then(|superclass| superclass.find_class_method(name)),
+        }
+    }
+
+    /// Property access on the class object itself, which can only reach class methods
+    pub fn get(class: &Rc<LoxClass>, name: &Token, interpreter: &mut Interpreter) -> Result<Object, LoxError> {
+        match class.find_class_method(&name.lexeme) {
+            Some(method) => method.bind_property(Object::Class(class.clone()), interpreter),
+            None => Err(LoxError::Runtime {
+                expected: format!("class method named {} on {}", name.lexeme, class.name),
+                found: "no such method".into(),
+                line: Some(name.line),
+            }),
+        }
+    }
+
+    /// A private class method of `owner`, called through `this` in one of its class methods
+    pub fn get_private(
+        class: &Rc<LoxClass>,
+        owner: &Rc<LoxClass>,
+        name: &Token,
+        interpreter: &mut Interpreter,
+    ) -> Result<Object, LoxError> {
+        match owner.class_methods.get(&name.lexeme) {
+            Some(method) => method.bind_property(Object::Class(class.clone()), interpreter),
+            None => Err(LoxError::Runtime {
+                expected: format!("private class method named {} on {}", name.lexeme, owner.name),
+                found: "no such private method".into(),
+                line: Some(name.line),
+            }),
+        }
     }
-}
 
-impl LoxCallable for LoxClass {
-    fn call(&self, _interpreter: &mut Interpreter, _arguments: Vec<Object>) -> Result<Object, LoxError> {
-        Ok(Object::Instance(LoxInstance::new(self.clone())))
+    /// Whether this is `other`, or inherits from it somewhere up the superclass chain
+    pub fn is_subclass_of(&self, other: &LoxClass) -> bool {
+        std::ptr::eq(self, other) || self.superclass.as_ref().is_some_and(|superclass| superclass.is_subclass_of(other))
     }
 
-    fn arity(&self) -> u8 {
-        0
+    /// Calling a class creates a new instance of it, running `init` if there is one. Instances keep
+    /// a reference to the class itself rather than a copy, so identity checks work
+    pub fn instantiate(class: &Rc<LoxClass>, interpreter: &mut Interpreter, arguments: Vec<Object>) -> Result<Object, LoxError> {
+        let instance = Rc::new(RefCell::new(LoxInstance::new(class.clone())));
+        if let Some(initializer) = class.find_method("init") {
+            initializer.bind(Object::Instance(instance.clone()))?.call(interpreter, arguments)?;
+        }
+        Ok(Object::Instance(instance))
     }
 
-    fn name(&self) -> &str {
-        &self.name
+    pub fn arity(&self) -> Arity {
+        self.find_method("init")
+            .map_or(Arity::exactly(0), |initializer| initializer.arity())
     }
 }
 
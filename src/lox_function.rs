@@ -7,9 +7,9 @@ use crate::{
     interpreter::{
         Interpreter,
         environment::{Environment, RcCell},
+        generator::LoxGenerator,
     },
-    lox_callable::LoxCallable,
-    lox_instance::LoxInstance,
+    lox_callable::{Arity, LoxCallable},
     object::{Literal, Object},
     stmt::Function,
 };
@@ -18,22 +18,52 @@ use crate::{
 pub struct LoxFunction {
     declaration: Function,
     closure: RcCell<Environment>,
+    is_initializer: bool,
 }
 
 impl LoxFunction {
-    pub fn new(declaration: Function, closure: RcCell<Environment>) -> Self {
-        Self { declaration, closure }
+    pub fn new(declaration: Function, closure: RcCell<Environment>, is_initializer: bool) -> Self {
+        Self {
+            declaration,
+            closure,
+            is_initializer,
+        }
+    }
+
+    /// Whether this is a getter, to be called when accessed rather than returned
+    pub fn is_getter(&self) -> bool {
+        self.declaration.getter
     }
 
-    #[instrument(skip(self, instance))]
-    pub(crate) fn bind(&self, instance: &LoxInstance) -> Result<Object, LoxError> {
+    /// Make a copy of this method where `this` refers to the given instance, or class for class methods
+    #[instrument(skip(self, this))]
+    pub(crate) fn bind(&self, this: Object) -> Result<Object, LoxError> {
         let mut environment = Environment::with_parent(self.closure.clone());
-        environment.define("this".into(), Object::Instance(instance.clone()));
+        environment.define("this".into(), this);
         trace!(vals = ?environment.values, "After binding this");
 
         let environment = Rc::new(RefCell::new(environment));
 
-        Ok(Object::Callable(Rc::new(LoxFunction::new(self.declaration.clone(), environment))))
+        Ok(Object::Callable(Rc::new(LoxFunction::new(
+            self.declaration.clone(),
+            environment,
+            self.is_initializer,
+        ))))
+    }
+
+    /// Bind a method being accessed as a property. Getters run straight away and give back their
+    /// result, anything else gives back the bound method
+    pub(crate) fn bind_property(&self, this: Object, interpreter: &mut Interpreter) -> Result<Object, LoxError> {
+        let method = self.bind(this)?;
+        if self.is_getter() {
+            return method.call(interpreter, Vec::new());
+        }
+        Ok(method)
+    }
+
+    /// An initializer always hands back the instance it was bound to
+    fn bound_this(&self) -> Result<Object, LoxError> {
+        self.closure.borrow_mut().get_at(&0, "this")
     }
 }
 
@@ -46,27 +76,49 @@ impl std::fmt::Display for LoxFunction {
 impl LoxCallable for LoxFunction {
     #[instrument(skip(self, interpreter), err)]
     fn call(&self, interpreter: &mut Interpreter, arguments: Vec<Object>) -> Result<Object, LoxError> {
-        let mut environment = Environment::with_parent(self.closure.clone());
+        let environment = Rc::new(RefCell::new(Environment::with_parent(self.closure.clone())));
         trace!(?environment, "Initial enclosed environment");
-        arguments.into_iter().enumerate().for_each(|(i, arg)| {
-            let name = self.declaration.params[i].lexeme.clone();
-            trace!(name, ?arg, "Defining additional argument in environment");
-            environment.define(name, arg);
-        });
+        let mut arguments = arguments.into_iter();
+        for (param, default) in self.declaration.params.iter().zip(self.declaration.defaults.iter()) {
+            // Defaults run in the call's own environment, so they can refer to the parameters before them
+            let arg = match (arguments.next(), default) {
+                (Some(arg), _) => arg,
+                (None, Some(default)) => interpreter.evaluate_in(default, environment.clone())?,
+                (None, None) => Object::Literal(Literal::Null),
+            };
+            trace!(name = param.lexeme, ?arg, "Defining additional argument in environment");
+            environment.borrow_mut().define(param.lexeme.clone(), arg);
+        }
+        if let Some(rest) = &self.declaration.rest {
+            let rest_args = Object::List(Rc::new(RefCell::new(arguments.collect())));
+            environment.borrow_mut().define(rest.lexeme.clone(), rest_args);
+        }
         trace!(?environment, "Environment for call");
 
-        match interpreter.execute_block(&self.declaration.body, environment).map_err(|e| match e {
-            LoxError::Return { value } => Ok(value),
-            other => Err(other),
-        }) {
+        if self.declaration.generator {
+            let generator = LoxGenerator::new(self.name(), self.declaration.body.clone(), environment);
+            return Ok(Object::Generator(Rc::new(RefCell::new(generator))));
+        }
+
+        match interpreter
+            .execute_block_in(&self.declaration.body, environment)
+            .map_err(|e| match e {
+                LoxError::Return { value } => Ok(value),
+                other => Err(other),
+            }) {
+            Ok(()) | Err(Ok(_)) if self.is_initializer => self.bound_this(),
             Ok(()) => Ok(Object::Literal(Literal::Null)),
             Err(Ok(value)) => Ok(value),
             Err(Err(e)) => Err(e),
         }
     }
 
-    fn arity(&self) -> u8 {
-        self.declaration.params.len() as u8
+    fn arity(&self) -> Arity {
+        let params = self.declaration.params.len() as u8;
+        Arity {
+            min: self.declaration.defaults.iter().filter(|default| default.is_none()).count() as u8,
+            max: self.declaration.rest.is_none().then_some(params),
+        }
     }
 
     fn name(&self) -> &str {
@@ -1,35 +1,49 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, rc::Rc};
 
 use tracing::trace;
 
-use crate::{LoxError, lox_class::LoxClass, object::Object, token::Token};
+use crate::{
+    LoxError,
+    interpreter::{Interpreter, environment::RcCell},
+    lox_class::LoxClass,
+    object::Object,
+    token::Token,
+};
 
 #[derive(Clone, Debug)]
 pub struct LoxInstance {
-    class: LoxClass,
+    pub class: Rc<LoxClass>,
     fields: HashMap<String, Object>,
+    /// Private fields, kept apart for each class that sets them so a subclass can't see its
+    /// superclass's, or clobber them with its own
+    private_fields: HashMap<(*const LoxClass, String), Object>,
 }
 
 impl LoxInstance {
-    pub fn new(class: LoxClass) -> Self {
+    pub fn new(class: Rc<LoxClass>) -> Self {
         Self {
             class,
             fields: HashMap::new(),
+            private_fields: HashMap::new(),
         }
     }
 
-    pub fn get(&self, name: &Token) -> Result<Object, LoxError> {
-        trace!(fields = ?self.fields, ?name, class = ?self.class, "LoxInstance.get()");
-        let field = self.fields.get(&name.lexeme).cloned();
+    /// Takes the shared instance rather than `&self` so that methods can be bound to it, and the
+    /// interpreter so that getters can run
+    pub fn get(instance: &RcCell<LoxInstance>, name: &Token, interpreter: &mut Interpreter) -> Result<Object, LoxError> {
+        let (field, method) = {
+            let this = instance.borrow();
+            trace!(fields = ?this.fields, ?name, class = ?this.class, "LoxInstance.get()");
+            (this.fields.get(&name.lexeme).cloned(), this.class.find_method(&name.lexeme))
+        };
         if let Some(field) = field {
             trace!(?field, "<<LoxInstance.get(), SUCCESS(field)");
             return Ok(field);
         }
 
-        let method = self.class.find_method(&name.lexeme);
         if let Some(method) = method {
             trace!(?method, "<<LoxInstance.get(), SUCCESS(method)");
-            return method.bind(self);
+            return method.bind_property(Object::Instance(instance.clone()), interpreter);
         }
 
         Err(LoxError::Runtime {
@@ -39,20 +53,58 @@ impl LoxInstance {
         })
     }
 
-    pub fn set(&mut self, name: Token, value: Object) {
+    /// A private field or method, as seen from the methods of `owner`. Private methods aren't
+    /// inherited, so only `owner`'s own are looked at
+    pub fn get_private(
+        instance: &RcCell<LoxInstance>,
+        owner: &Rc<LoxClass>,
+        name: &Token,
+        interpreter: &mut Interpreter,
+    ) -> Result<Object, LoxError> {
+        let field = instance
+            .borrow()
+            .private_fields
+            .get(&(Rc::as_ptr(owner), name.lexeme.clone()))
+            .cloned();
+        if let Some(field) = field {
+            return Ok(field);
+        }
+
+        match owner.methods.get(&name.lexeme) {
+            Some(method) => method.bind_property(Object::Instance(instance.clone()), interpreter),
+            None => Err(LoxError::Runtime {
+                expected: format!("private method or field named {} on {}", name.lexeme, owner.name),
+                found: "no such private method or field".into(),
+                line: Some(name.line),
+            }),
+        }
+    }
+
+    pub fn set_private(&mut self, owner: &Rc<LoxClass>, name: &str, value: Object) {
+        self.private_fields.insert((Rc::as_ptr(owner), name.to_string()), value);
+    }
+
+    /// A field only, without falling back to methods
+    pub fn field(&self, name: &str) -> Option<&Object> {
+        self.fields.get(name)
+    }
+
+    pub fn set(&mut self, name: &str, value: Object) {
         trace!(fields = ?self.fields, ?name, class = ?self.class, value = ?value, "LoxInstance.set()");
-        self.fields.insert(name.lexeme, value);
+        self.fields.insert(name.to_string(), value);
     }
 }
 
 impl std::fmt::Display for LoxInstance {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "[Object {}] - {:?}", self.class, self.fields)
+        write!(f, "<{} instance>", self.class)
     }
 }
 
 #[cfg(test)]
 mod test {
+    use std::cell::RefCell;
+
     use pretty_assertions::{assert_eq, assert_matches};
 
     use super::*;
@@ -65,10 +117,15 @@ mod test {
     fn gets_and_sets() {
         let token = Token::new(TokenType::Identifier, "foo", Literal::Null, 0);
         let obj = Object::Literal(Literal::from(42));
-        let mut instance = LoxInstance::new(LoxClass::new("fake", HashMap::new()));
-
-        instance.set(token.clone(), obj.clone());
-        let got = instance.get(&token).unwrap();
+        let instance = Rc::new(RefCell::new(LoxInstance::new(Rc::new(LoxClass::new(
+            "fake",
+            None,
+            HashMap::new(),
+            HashMap::new(),
+        )))));
+
+        instance.borrow_mut().set(&token.lexeme, obj.clone());
+        let got = LoxInstance::get(&instance, &token, &mut Interpreter::new()).unwrap();
         assert_eq!(got, obj);
     }
 
@@ -76,33 +133,48 @@ mod test {
     fn only_cares_about_lexeme() {
         let token = Token::new(TokenType::Identifier, "foo", Literal::Null, 0);
         let obj = Object::Literal(Literal::from(42));
-        let mut instance = LoxInstance::new(LoxClass::new("fake", HashMap::new()));
-        instance.set(token.clone(), obj.clone());
+        let instance = Rc::new(RefCell::new(LoxInstance::new(Rc::new(LoxClass::new(
+            "fake",
+            None,
+            HashMap::new(),
+            HashMap::new(),
+        )))));
+        instance.borrow_mut().set(&token.lexeme, obj.clone());
 
         let other_token = Token::new(TokenType::LeftParen, "foo", Literal::from(666), 42);
-        let got = instance.get(&other_token).unwrap();
+        let got = LoxInstance::get(&instance, &other_token, &mut Interpreter::new()).unwrap();
         assert_eq!(got, obj);
     }
 
     #[test]
     fn errors_when_missing() {
         let token = Token::new(TokenType::Identifier, "foo", Literal::Null, 0);
-        let instance = LoxInstance::new(LoxClass::new("fake", HashMap::new()));
-
-        let got = instance.get(&token);
-        assert_matches!(got, Err(LoxError::Internal { .. }));
+        let instance = Rc::new(RefCell::new(LoxInstance::new(Rc::new(LoxClass::new(
+            "fake",
+            None,
+            HashMap::new(),
+            HashMap::new(),
+        )))));
+
+        let got = LoxInstance::get(&instance, &token, &mut Interpreter::new());
+        assert_matches!(got, Err(LoxError::Runtime { .. }));
     }
 
     #[test]
     fn replaces_when_setting_over() {
         let token = Token::new(TokenType::Identifier, "foo", Literal::Null, 0);
         let obj = Object::Literal(Literal::from(42));
-        let mut instance = LoxInstance::new(LoxClass::new("fake", HashMap::new()));
-        instance.set(token.clone(), obj.clone());
+        let instance = Rc::new(RefCell::new(LoxInstance::new(Rc::new(LoxClass::new(
+            "fake",
+            None,
+            HashMap::new(),
+            HashMap::new(),
+        )))));
+        instance.borrow_mut().set(&token.lexeme, obj.clone());
 
         let other_obj = Object::Literal(Literal::from("42"));
-        instance.set(token.clone(), other_obj.clone());
-        let got = instance.get(&token).unwrap();
+        instance.borrow_mut().set(&token.lexeme, other_obj.clone());
+        let got = LoxInstance::get(&instance, &token, &mut Interpreter::new()).unwrap();
         assert_eq!(got, other_obj);
     }
 }
@@ -0,0 +1,40 @@
+use crate::{
+    LoxError,
+    interpreter::environment::{Environment, RcCell},
+    object::Object,
+    token::Token,
+};
+
+/// A Lox file loaded with `import`. Its top-level definitions live in its own global environment
+#[derive(Debug)]
+pub struct LoxModule {
+    pub name: String,
+    globals: RcCell<Environment>,
+}
+
+impl LoxModule {
+    pub fn new<T>(name: T, globals: RcCell<Environment>) -> Self
+    where
+        T: Into<String>,
+    {
+        Self {
+            name: name.into(),
+            globals,
+        }
+    }
+
+    /// Look up something defined at the top level of the module
+    pub fn get(&self, name: &Token) -> Result<Object, LoxError> {
+        self.globals.borrow().values.get(&name.lexeme).cloned().ok_or(LoxError::Runtime {
+            expected: format!("'{}' to be defined by module {}", name.lexeme, self.name),
+            found: "undefined".into(),
+            line: Some(name.line),
+        })
+    }
+}
+
+impl std::fmt::Display for LoxModule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<module {}>", self.name)
+    }
+}
@@ -5,6 +5,7 @@ mod lox_callable;
 mod lox_class;
 mod lox_function;
 mod lox_instance;
+mod lox_module;
 mod native;
 mod object;
 mod parser;
@@ -82,6 +83,7 @@ impl Lox {
     }
 
     pub fn run_file<T: AsRef<Path> + Into<String>>(&mut self, script_path: T) -> i32 {
+        self.interpreter.set_script(script_path.as_ref());
         let file = std::fs::read_to_string(&script_path)
             .context(FileSnafu { path: script_path.into() })
             .expect("Cannot read file");
@@ -165,6 +167,14 @@ pub enum LoxError {
     Internal { message: String },
     #[snafu()]
     Return { value: Object },
+    #[snafu()]
+    Break { label: Option<String> },
+    #[snafu()]
+    Continue { label: Option<String> },
+    #[snafu(display("Could not import '{path}': {message}\n[line {line}]"))]
+    Import { path: String, message: String, line: usize },
+    #[snafu(display("Uncaught exception: {value}\n[line {line}]"))]
+    Throw { value: Object, line: usize },
     #[snafu(whatever, display("Static analysis failed: {message}, {source:?}, {loc}"))]
     Resolver {
         message: String,
@@ -176,12 +186,18 @@ pub enum LoxError {
 }
 
 impl LoxError {
+    /// Fill in the line of a runtime error that doesn't know where it happened yet. One that
+    /// already has a line keeps it, so errors from inside a call point at the failing statement
     pub fn add_line(self, line: usize) -> LoxError {
         match self {
-            LoxError::Runtime { found, expected, line: _ } => LoxError::Runtime {
+            LoxError::Runtime {
+                found,
+                expected,
+                line: existing,
+            } => LoxError::Runtime {
                 found,
                 expected,
-                line: Some(line),
+                line: existing.or(Some(line)),
             },
             _ => self,
         }
@@ -1,7 +1,7 @@
 use crate::{
     LoxError,
     interpreter::Interpreter,
-    lox_callable::LoxCallable,
+    lox_callable::{Arity, LoxCallable},
     object::{Literal, Object},
 };
 
@@ -9,12 +9,12 @@ pub struct LoxAssertEq {}
 
 impl std::fmt::Display for LoxAssertEq {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "<native function>")
+        write!(f, "<native fn {}>", self.name())
     }
 }
 
 impl LoxCallable for LoxAssertEq {
-    fn call(&self, _interpreter: &mut Interpreter, arguments: Vec<Object>) -> Result<Object, LoxError> {
+    fn call(&self, interpreter: &mut Interpreter, arguments: Vec<Object>) -> Result<Object, LoxError> {
         if arguments.len() != 2 {
             return Err(LoxError::Runtime {
                 found: format!("{} args", arguments.len()),
@@ -24,7 +24,7 @@ impl LoxCallable for LoxAssertEq {
         }
         let first = arguments.first().expect("already checked");
         let second = arguments.get(1).expect("already checked");
-        if first == second {
+        if interpreter.values_equal(first, second, None)? {
             Ok(Object::Literal(Literal::Null))
         } else {
             Err(LoxError::Runtime {
@@ -35,11 +35,11 @@ impl LoxCallable for LoxAssertEq {
         }
     }
 
-    fn arity(&self) -> u8 {
-        2
+    fn arity(&self) -> Arity {
+        Arity::exactly(2)
     }
 
     fn name(&self) -> &'static str {
-        "assert"
+        "assert_eq"
     }
 }
@@ -2,13 +2,18 @@ use std::time::SystemTime;
 
 use ordered_float::OrderedFloat;
 
-use crate::{LoxError, interpreter::Interpreter, lox_callable::LoxCallable, object::Object};
+use crate::{
+    LoxError,
+    interpreter::Interpreter,
+    lox_callable::{Arity, LoxCallable},
+    object::Object,
+};
 
 pub struct LoxClock {}
 
 impl std::fmt::Display for LoxClock {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "<native function>")
+        write!(f, "<native fn {}>", self.name())
     }
 }
 
@@ -22,11 +27,11 @@ impl LoxCallable for LoxClock {
         )))
     }
 
-    fn arity(&self) -> u8 {
-        0
+    fn arity(&self) -> Arity {
+        Arity::exactly(0)
     }
 
     fn name(&self) -> &'static str {
-        "system_clock"
+        "clock"
     }
 }
@@ -0,0 +1,41 @@
+use crate::{
+    LoxError,
+    interpreter::Interpreter,
+    lox_callable::{Arity, LoxCallable},
+    object::{Literal, Object},
+};
+
+pub struct LoxLen {}
+
+impl std::fmt::Display for LoxLen {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<native fn {}>", self.name())
+    }
+}
+
+impl LoxCallable for LoxLen {
+    fn call(&self, _interpreter: &mut Interpreter, arguments: Vec<Object>) -> Result<Object, LoxError> {
+        let [value] = <[Object; 1]>::try_from(arguments).expect("arity already checked");
+        let len = match value {
+            Object::List(list) => list.borrow().len(),
+            Object::Map(map) => map.borrow().len(),
+            Object::Literal(Literal::String(s)) => s.chars().count(),
+            other => {
+                return Err(LoxError::Runtime {
+                    found: other.to_string(),
+                    expected: "a list, map or string".into(),
+                    line: None,
+                });
+            }
+        };
+        Ok(Object::from(len as i64))
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::exactly(1)
+    }
+
+    fn name(&self) -> &'static str {
+        "len"
+    }
+}
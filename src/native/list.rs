@@ -0,0 +1,167 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    LoxError,
+    interpreter::{Interpreter, environment::RcCell},
+    lox_callable::{Arity, LoxCallable},
+    native::map::remove_entry,
+    object::{Literal, Object, list_index},
+};
+
+fn expect_list(object: &Object) -> Result<RcCell<Vec<Object>>, LoxError> {
+    match object {
+        Object::List(list) => Ok(list.clone()),
+        other => Err(LoxError::Runtime {
+            found: other.to_string(),
+            expected: "a list".into(),
+            line: None,
+        }),
+    }
+}
+
+/// `push(list, value)` appends to the end of the list
+pub struct LoxPush {}
+
+impl std::fmt::Display for LoxPush {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<native fn {}>", self.name())
+    }
+}
+
+impl LoxCallable for LoxPush {
+    fn call(&self, _interpreter: &mut Interpreter, arguments: Vec<Object>) -> Result<Object, LoxError> {
+        let [list, value] = <[Object; 2]>::try_from(arguments).expect("arity already checked");
+        expect_list(&list)?.borrow_mut().push(value);
+        Ok(Object::Literal(Literal::Null))
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::exactly(2)
+    }
+
+    fn name(&self) -> &'static str {
+        "push"
+    }
+}
+
+/// `pop(list)` removes and returns the last element
+pub struct LoxPop {}
+
+impl std::fmt::Display for LoxPop {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<native fn {}>", self.name())
+    }
+}
+
+impl LoxCallable for LoxPop {
+    fn call(&self, _interpreter: &mut Interpreter, arguments: Vec<Object>) -> Result<Object, LoxError> {
+        let [list] = <[Object; 1]>::try_from(arguments).expect("arity already checked");
+        expect_list(&list)?.borrow_mut().pop().ok_or(LoxError::Runtime {
+            found: "an empty list".into(),
+            expected: "a list with at least one element to pop".into(),
+            line: None,
+        })
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::exactly(1)
+    }
+
+    fn name(&self) -> &'static str {
+        "pop"
+    }
+}
+
+/// `insert(list, index, value)` puts `value` before the element currently at `index`
+pub struct LoxInsert {}
+
+impl std::fmt::Display for LoxInsert {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<native fn {}>", self.name())
+    }
+}
+
+impl LoxCallable for LoxInsert {
+    fn call(&self, _interpreter: &mut Interpreter, arguments: Vec<Object>) -> Result<Object, LoxError> {
+        let [list, index, value] = <[Object; 3]>::try_from(arguments).expect("arity already checked");
+        let list = expect_list(&list)?;
+        let mut list = list.borrow_mut();
+        let position = list_index(&index, list.len(), true)?;
+        list.insert(position, value);
+        Ok(Object::Literal(Literal::Null))
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::exactly(3)
+    }
+
+    fn name(&self) -> &'static str {
+        "insert"
+    }
+}
+
+/// `remove(list, index)` removes and returns the element at `index`, `remove(map, key)` does the
+/// same for the entry at `key`
+pub struct LoxRemove {}
+
+impl std::fmt::Display for LoxRemove {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<native fn {}>", self.name())
+    }
+}
+
+impl LoxCallable for LoxRemove {
+    fn call(&self, interpreter: &mut Interpreter, arguments: Vec<Object>) -> Result<Object, LoxError> {
+        let [list, index] = <[Object; 2]>::try_from(arguments).expect("arity already checked");
+        if let Object::Map(map) = &list {
+            return remove_entry(interpreter, map, &index);
+        }
+        let list = expect_list(&list)?;
+        let mut list = list.borrow_mut();
+        let position = list_index(&index, list.len(), false)?;
+        Ok(list.remove(position))
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::exactly(2)
+    }
+
+    fn name(&self) -> &'static str {
+        "remove"
+    }
+}
+
+/// `slice(list, start, end)` copies the elements from `start` up to, but not including, `end`
+pub struct LoxSlice {}
+
+impl std::fmt::Display for LoxSlice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<native fn {}>", self.name())
+    }
+}
+
+impl LoxCallable for LoxSlice {
+    fn call(&self, _interpreter: &mut Interpreter, arguments: Vec<Object>) -> Result<Object, LoxError> {
+        let [list, start, end] = <[Object; 3]>::try_from(arguments).expect("arity already checked");
+        let list = expect_list(&list)?;
+        let list = list.borrow();
+        let start = list_index(&start, list.len(), true)?;
+        let end = list_index(&end, list.len(), true)?;
+        if start > end {
+            return Err(LoxError::Runtime {
+                found: format!("start {start} after end {end}"),
+                expected: "a slice start no greater than its end".into(),
+                line: None,
+            });
+        }
+        Ok(Object::List(Rc::new(RefCell::new(list[start..end].to_vec()))))
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::exactly(3)
+    }
+
+    fn name(&self) -> &'static str {
+        "slice"
+    }
+}
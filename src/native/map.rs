@@ -0,0 +1,112 @@
+use std::{cell::RefCell, rc::Rc};
+
+use indexmap::IndexMap;
+
+use crate::{
+    LoxError,
+    interpreter::{Interpreter, environment::RcCell},
+    lox_callable::{Arity, LoxCallable},
+    object::{MapKey, Object},
+};
+
+fn expect_map(object: &Object) -> Result<RcCell<IndexMap<MapKey, Object>>, LoxError> {
+    match object {
+        Object::Map(map) => Ok(map.clone()),
+        other => Err(LoxError::Runtime {
+            found: other.to_string(),
+            expected: "a map".into(),
+            line: None,
+        }),
+    }
+}
+
+/// `keys(map)` lists the keys of a map, in insertion order
+pub struct LoxKeys {}
+
+impl std::fmt::Display for LoxKeys {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<native fn {}>", self.name())
+    }
+}
+
+impl LoxCallable for LoxKeys {
+    fn call(&self, _interpreter: &mut Interpreter, arguments: Vec<Object>) -> Result<Object, LoxError> {
+        let [map] = <[Object; 1]>::try_from(arguments).expect("arity already checked");
+        let keys = expect_map(&map)?.borrow().keys().map(MapKey::to_object).collect();
+        Ok(Object::List(Rc::new(RefCell::new(keys))))
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::exactly(1)
+    }
+
+    fn name(&self) -> &'static str {
+        "keys"
+    }
+}
+
+/// `values(map)` lists the values of a map, in insertion order
+pub struct LoxValues {}
+
+impl std::fmt::Display for LoxValues {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<native fn {}>", self.name())
+    }
+}
+
+impl LoxCallable for LoxValues {
+    fn call(&self, _interpreter: &mut Interpreter, arguments: Vec<Object>) -> Result<Object, LoxError> {
+        let [map] = <[Object; 1]>::try_from(arguments).expect("arity already checked");
+        let values = expect_map(&map)?.borrow().values().cloned().collect();
+        Ok(Object::List(Rc::new(RefCell::new(values))))
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::exactly(1)
+    }
+
+    fn name(&self) -> &'static str {
+        "values"
+    }
+}
+
+/// `has(map, key)` checks whether a map has an entry for `key`
+pub struct LoxHas {}
+
+impl std::fmt::Display for LoxHas {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<native fn {}>", self.name())
+    }
+}
+
+impl LoxCallable for LoxHas {
+    fn call(&self, interpreter: &mut Interpreter, arguments: Vec<Object>) -> Result<Object, LoxError> {
+        let [map, key] = <[Object; 2]>::try_from(arguments).expect("arity already checked");
+        let map = expect_map(&map)?;
+        let key = interpreter.map_key(&map, &key, None)?;
+        Ok(map.borrow().contains_key(&key).into())
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::exactly(2)
+    }
+
+    fn name(&self) -> &'static str {
+        "has"
+    }
+}
+
+/// Removes the entry for `key`, returning its value. Backs `remove(map, key)`
+pub(crate) fn remove_entry(
+    interpreter: &mut Interpreter,
+    map: &RcCell<IndexMap<MapKey, Object>>,
+    key: &Object,
+) -> Result<Object, LoxError> {
+    let key = interpreter.map_key(map, key, None)?;
+    // `shift_remove` keeps the remaining entries in insertion order
+    map.borrow_mut().shift_remove(&key).ok_or(LoxError::Runtime {
+        found: format!("no entry for {key}"),
+        expected: "a key present in the map".into(),
+        line: None,
+    })
+}
@@ -1,2 +1,6 @@
 pub mod assert_eq;
 pub mod clock;
+pub mod len;
+pub mod list;
+pub mod map;
+pub mod string;
@@ -0,0 +1,31 @@
+use crate::{
+    LoxError,
+    interpreter::Interpreter,
+    lox_callable::{Arity, LoxCallable},
+    object::{Literal, Object},
+};
+
+/// `str(value)`, the same conversion `print` and string concatenation use
+pub struct LoxStr {}
+
+impl std::fmt::Display for LoxStr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<native fn {}>", self.name())
+    }
+}
+
+impl LoxCallable for LoxStr {
+    fn call(&self, interpreter: &mut Interpreter, arguments: Vec<Object>) -> Result<Object, LoxError> {
+        let [value] = <[Object; 1]>::try_from(arguments).expect("arity already checked");
+        // The call fills in its own line on any errors
+        Ok(Object::Literal(Literal::String(interpreter.stringify(&value, None)?)))
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::exactly(1)
+    }
+
+    fn name(&self) -> &'static str {
+        "str"
+    }
+}
@@ -1,16 +1,145 @@
 use std::{cmp, fmt, ops, rc::Rc};
 
+use indexmap::IndexMap;
 use ordered_float::OrderedFloat;
 
-use crate::{LoxError, interpreter::Interpreter, lox_callable::LoxCallable, lox_instance::LoxInstance};
+use crate::{
+    LoxError,
+    interpreter::{Interpreter, environment::RcCell, generator::LoxGenerator},
+    lox_callable::{Arity, LoxCallable},
+    lox_class::LoxClass,
+    lox_instance::LoxInstance,
+    lox_module::LoxModule,
+};
 
 #[derive(Clone)]
 pub enum Object {
     Callable(Rc<dyn LoxCallable>),
-    Instance(LoxInstance),
+    Class(Rc<LoxClass>),
+    Instance(RcCell<LoxInstance>),
+    List(RcCell<Vec<Object>>),
+    /// Keyed by literals and instances, and iterates in insertion order
+    Map(RcCell<IndexMap<MapKey, Object>>),
+    Module(Rc<LoxModule>),
+    Generator(RcCell<LoxGenerator>),
     Literal(Literal),
 }
 
+impl Object {
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Object::Literal(literal) => literal.is_truthy(),
+            _ => true,
+        }
+    }
+
+    /// Where a list or map lives, to spot one that contains itself
+    pub fn collection_ptr(&self) -> Option<*const ()> {
+        match self {
+            Object::List(list) => Some(Rc::as_ptr(list) as *const ()),
+            Object::Map(map) => Some(Rc::as_ptr(map) as *const ()),
+            _ => None,
+        }
+    }
+
+    /// `~/` has no operator trait to hang off of, see [`Literal::integer_div`]
+    pub fn integer_div(self, rhs: Self) -> Result<Object, LoxError> {
+        let (first, second) = self.literals(rhs, "Number ~/ Number")?;
+        Ok(Object::Literal(first.integer_div(second)?))
+    }
+
+    /// `**`, see [`Literal::pow`]
+    pub fn pow(self, rhs: Self) -> Result<Object, LoxError> {
+        let (first, second) = self.literals(rhs, "Number ** Number")?;
+        Ok(Object::Literal(first.pow(second)?))
+    }
+
+    /// Arithmetic and bitwise operators only work on literals
+    fn literals(self, rhs: Self, expected: &str) -> Result<(Literal, Literal), LoxError> {
+        match (self, rhs) {
+            (Self::Literal(first), Self::Literal(second)) => Ok((first, second)),
+            _ => Err(LoxError::Runtime {
+                found: "non-literal operands".into(),
+                expected: expected.to_string(),
+                line: None,
+            }),
+        }
+    }
+}
+
+/// Turn a (possibly negative, counting from the end) Lox index into a position in a list of `len`
+/// elements. `len` itself is only a valid position when `allow_end` is set, e.g. for inserting
+pub fn list_index(index: &Object, len: usize, allow_end: bool) -> Result<usize, LoxError> {
+    let out_of_range = || LoxError::Runtime {
+        found: format!("index {index}"),
+        expected: format!("an index in range for a list of length {len}"),
+        line: None,
+    };
+
+    let n = match index {
+        Object::Literal(Literal::Integer(n)) => *n,
+        Object::Literal(Literal::Number(n)) if n.fract() == 0.0 => **n as i64,
+        _ => {
+            return Err(LoxError::Runtime {
+                found: index.to_string(),
+                expected: "a whole number index".to_string(),
+                line: None,
+            });
+        }
+    };
+    let position = if n < 0 { len as i64 + n } else { n };
+    let upper = if allow_end { len as i64 } else { len as i64 - 1 };
+    if position < 0 || position > upper {
+        return Err(out_of_range());
+    }
+    Ok(position as usize)
+}
+
+/// What a map is keyed by. Literals hash and compare by value, instances by identity. Instances whose
+/// class defines `hash()` and `equals()` are swapped for the existing key they're equal to before
+/// they get here, see `Interpreter::map_key`
+#[derive(Clone)]
+pub enum MapKey {
+    Literal(Literal),
+    Instance { instance: RcCell<LoxInstance>, hash: u64 },
+}
+
+impl MapKey {
+    pub fn to_object(&self) -> Object {
+        match self {
+            MapKey::Literal(literal) => Object::Literal(literal.clone()),
+            MapKey::Instance { instance, .. } => Object::Instance(instance.clone()),
+        }
+    }
+}
+
+impl cmp::PartialEq for MapKey {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (MapKey::Literal(a), MapKey::Literal(b)) => a == b,
+            (MapKey::Instance { instance: a, .. }, MapKey::Instance { instance: b, .. }) => Rc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for MapKey {}
+
+impl std::hash::Hash for MapKey {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        match self {
+            MapKey::Literal(literal) => literal.hash(state),
+            MapKey::Instance { hash, .. } => hash.hash(state),
+        }
+    }
+}
+
+impl fmt::Display for MapKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_object())
+    }
+}
+
 impl From<bool> for Object {
     fn from(value: bool) -> Self {
         Object::Literal(Literal::Boolean(value))
@@ -23,6 +152,12 @@ impl From<f64> for Object {
     }
 }
 
+impl From<i64> for Object {
+    fn from(value: i64) -> Self {
+        Object::Literal(Literal::Integer(value))
+    }
+}
+
 impl From<OrderedFloat<f64>> for Object {
     fn from(value: OrderedFloat<f64>) -> Self {
         Object::Literal(Literal::Number(value))
@@ -36,7 +171,25 @@ impl fmt::Debug for Object {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Object::Callable(c) => write!(f, "{c}"),
-            Object::Instance(c) => write!(f, "{c}"),
+            Object::Class(c) => write!(f, "{c}"),
+            // Instances can refer to themselves, so don't descend into their fields here
+            Object::Instance(c) => match c.try_borrow() {
+                Ok(instance) => write!(f, "<{} instance>", instance.class),
+                Err(_) => write!(f, "<instance>"),
+            },
+            Object::List(list) => match list.try_borrow() {
+                Ok(list) => write!(f, "<list of {}>", list.len()),
+                Err(_) => write!(f, "<list>"),
+            },
+            Object::Map(map) => match map.try_borrow() {
+                Ok(map) => write!(f, "<map of {}>", map.len()),
+                Err(_) => write!(f, "<map>"),
+            },
+            Object::Module(module) => write!(f, "{module}"),
+            Object::Generator(generator) => match generator.try_borrow() {
+                Ok(generator) => write!(f, "{generator}"),
+                Err(_) => write!(f, "<generator>"),
+            },
             Object::Literal(literal) => write!(f, "{literal:?}"),
         }
     }
@@ -46,13 +199,15 @@ impl LoxCallable for Object {
     fn call(&self, interpreter: &mut Interpreter, arguments: Vec<Object>) -> Result<Object, LoxError> {
         match self {
             Self::Callable(c) => c.call(interpreter, arguments),
+            Self::Class(c) => LoxClass::instantiate(c, interpreter, arguments),
             _ => panic!("{:?} is not a LoxCallable", &self),
         }
     }
 
-    fn arity(&self) -> u8 {
+    fn arity(&self) -> Arity {
         match self {
             Self::Callable(c) => c.arity(),
+            Self::Class(c) => c.arity(),
             _ => panic!("{:?} is not a LoxCallable", &self),
         }
     }
@@ -60,6 +215,7 @@ impl LoxCallable for Object {
     fn name(&self) -> &str {
         match self {
             Self::Callable(c) => c.name(),
+            Self::Class(c) => &c.name,
             _ => panic!("{:?} is not a LoxCallable", &self),
         }
     }
@@ -141,6 +297,75 @@ impl ops::Mul for Object {
     }
 }
 
+impl ops::Rem for Object {
+    type Output = Result<Object, LoxError>;
+
+    fn rem(self, rhs: Self) -> Self::Output {
+        let (first, second) = self.literals(rhs, "Number % Number")?;
+        Ok(Object::Literal((first % second)?))
+    }
+}
+
+impl ops::BitAnd for Object {
+    type Output = Result<Object, LoxError>;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        let (first, second) = self.literals(rhs, "Integer & Integer")?;
+        Ok(Object::Literal((first & second)?))
+    }
+}
+
+impl ops::BitOr for Object {
+    type Output = Result<Object, LoxError>;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        let (first, second) = self.literals(rhs, "Integer | Integer")?;
+        Ok(Object::Literal((first | second)?))
+    }
+}
+
+impl ops::BitXor for Object {
+    type Output = Result<Object, LoxError>;
+
+    fn bitxor(self, rhs: Self) -> Self::Output {
+        let (first, second) = self.literals(rhs, "Integer ^ Integer")?;
+        Ok(Object::Literal((first ^ second)?))
+    }
+}
+
+impl ops::Shl for Object {
+    type Output = Result<Object, LoxError>;
+
+    fn shl(self, rhs: Self) -> Self::Output {
+        let (first, second) = self.literals(rhs, "Integer << Integer")?;
+        Ok(Object::Literal((first << second)?))
+    }
+}
+
+impl ops::Shr for Object {
+    type Output = Result<Object, LoxError>;
+
+    fn shr(self, rhs: Self) -> Self::Output {
+        let (first, second) = self.literals(rhs, "Integer >> Integer")?;
+        Ok(Object::Literal((first >> second)?))
+    }
+}
+
+impl ops::Not for Object {
+    type Output = Result<Object, LoxError>;
+
+    fn not(self) -> Self::Output {
+        match self {
+            Self::Literal(literal) => Ok(Object::Literal((!literal)?)),
+            _ => Err(LoxError::Runtime {
+                found: self.to_string(),
+                expected: "an integer for '~'".to_string(),
+                line: None,
+            }),
+        }
+    }
+}
+
 impl cmp::PartialOrd for Object {
     fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
         match (self, other) {
@@ -152,39 +377,136 @@ impl cmp::PartialOrd for Object {
 
 impl cmp::PartialEq for Object {
     fn eq(&self, other: &Self) -> bool {
-        match (self, other) {
-            (Object::Callable(c1), Object::Callable(c2)) => c1.name() == c2.name() && c1.arity() == c2.arity(),
-            (Object::Literal(l1), Object::Literal(l2)) => l1 == l2,
-            _ => false,
+        objects_equal(self, other, &mut Vec::new())
+    }
+}
+
+/// `visited` holds the pairs of collections already being compared further up, so that a list
+/// containing itself falls back to identity rather than recursing forever
+fn objects_equal(first: &Object, second: &Object, visited: &mut Vec<(*const (), *const ())>) -> bool {
+    let pair = match (first.collection_ptr(), second.collection_ptr()) {
+        (Some(p1), Some(p2)) if p1 == p2 => return true,
+        (Some(p1), Some(p2)) if visited.contains(&(p1, p2)) => return false,
+        (Some(p1), Some(p2)) => Some((p1, p2)),
+        _ => None,
+    };
+    visited.extend(pair);
+    let equal = match (first, second) {
+        // Functions, instances and the like are only equal to themselves
+        (Object::Callable(c1), Object::Callable(c2)) => Rc::ptr_eq(c1, c2),
+        (Object::Instance(i1), Object::Instance(i2)) => Rc::ptr_eq(i1, i2),
+        (Object::Class(c1), Object::Class(c2)) => Rc::ptr_eq(c1, c2),
+        (Object::List(l1), Object::List(l2)) => {
+            let (l1, l2) = (l1.borrow(), l2.borrow());
+            l1.len() == l2.len() && l1.iter().zip(l2.iter()).all(|(e1, e2)| objects_equal(e1, e2, visited))
+        }
+        // Like `IndexMap`'s own equality, insertion order doesn't matter
+        (Object::Map(m1), Object::Map(m2)) => {
+            let (m1, m2) = (m1.borrow(), m2.borrow());
+            m1.len() == m2.len()
+                && m1
+                    .iter()
+                    .all(|(key, v1)| m2.get(key).is_some_and(|v2| objects_equal(v1, v2, visited)))
         }
+        (Object::Module(m1), Object::Module(m2)) => Rc::ptr_eq(m1, m2),
+        (Object::Generator(g1), Object::Generator(g2)) => Rc::ptr_eq(g1, g2),
+        (Object::Literal(l1), Object::Literal(l2)) => l1 == l2,
+        _ => false,
+    };
+    if pair.is_some() {
+        visited.pop();
     }
+    equal
 }
 
 impl fmt::Display for Object {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Object::Callable(c) => write!(f, "callable <{}>", c.name()),
-            Object::Instance(c) => write!(f, "{}", c),
-            Object::Literal(literal) => write!(f, "{literal}"),
+        write_object(f, self, &mut Vec::new())
+    }
+}
+
+/// `visited` holds the collections already being written further up, which are shown as `[...]`
+/// or `{...}` rather than written out again
+fn write_object(f: &mut fmt::Formatter<'_>, object: &Object, visited: &mut Vec<*const ()>) -> fmt::Result {
+    match object {
+        Object::Callable(c) => write!(f, "{c}"),
+        Object::Class(c) => write!(f, "<class {c}>"),
+        Object::Instance(c) => write!(f, "{}", c.borrow()),
+        Object::List(list) => {
+            let ptr = Rc::as_ptr(list) as *const ();
+            if visited.contains(&ptr) {
+                return write!(f, "[...]");
+            }
+            visited.push(ptr);
+            write!(f, "[")?;
+            for (i, element) in list.borrow().iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write_nested(f, element, visited)?;
+            }
+            visited.pop();
+            write!(f, "]")
+        }
+        Object::Map(map) => {
+            let ptr = Rc::as_ptr(map) as *const ();
+            if visited.contains(&ptr) {
+                return write!(f, "{{...}}");
+            }
+            visited.push(ptr);
+            write!(f, "{{")?;
+            for (i, (key, value)) in map.borrow().iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write_nested(f, &key.to_object(), visited)?;
+                write!(f, ": ")?;
+                write_nested(f, value, visited)?;
+            }
+            visited.pop();
+            write!(f, "}}")
         }
+        Object::Module(module) => write!(f, "{module}"),
+        Object::Generator(_) => write!(f, "{object:?}"),
+        Object::Literal(literal) => write!(f, "{literal}"),
     }
 }
 
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+/// Strings inside collections are quoted so `["a, b"]` can't be mistaken for `["a", "b"]`
+fn write_nested(f: &mut fmt::Formatter<'_>, element: &Object, visited: &mut Vec<*const ()>) -> fmt::Result {
+    match element {
+        Object::Literal(Literal::String(s)) => write!(f, "\"{s}\""),
+        other => write_object(f, other, visited),
+    }
+}
+
+#[derive(Clone, Debug)]
 pub enum Literal {
     String(String),
     Null,
+    /// A float, written with a fractional part or produced by mixing floats and integers
     Number(OrderedFloat<f64>),
+    Integer(i64),
     Boolean(bool),
 }
 
+/// The operands of an arithmetic operator. Two integers stay integers, but if either side is a
+/// float then both are treated as floats
+enum Numbers {
+    Integers(i64, i64),
+    Floats(f64, f64),
+}
+
 impl std::ops::Add for Literal {
     type Output = Result<Literal, LoxError>;
 
     fn add(self, rhs: Self) -> Self::Output {
         match (self, rhs) {
-            (Literal::Number(first), Literal::Number(second)) => Ok(Literal::Number(first + second)),
             (Literal::String(first), Literal::String(second)) => Ok(format!("{}{}", first, second).into()),
+            (first, second) if first.is_number() && second.is_number() => match first.numbers(second, "+")? {
+                Numbers::Integers(a, b) => checked(a.checked_add(b), "+"),
+                Numbers::Floats(a, b) => Ok((a + b).into()),
+            },
             _ => Err(LoxError::Runtime {
                 found: "mismatched operands".into(),
                 expected: "string + string, or number + number".into(),
@@ -198,13 +520,9 @@ impl std::ops::Sub for Literal {
     type Output = Result<Literal, LoxError>;
 
     fn sub(self, rhs: Self) -> Self::Output {
-        match (self, rhs) {
-            (Literal::Number(first), Literal::Number(second)) => Ok(Literal::Number(first - second)),
-            _ => Err(LoxError::Runtime {
-                found: "non-number operand(s)".into(),
-                expected: "number + number".into(),
-                line: None,
-            }),
+        match self.numbers(rhs, "-")? {
+            Numbers::Integers(a, b) => checked(a.checked_sub(b), "-"),
+            Numbers::Floats(a, b) => Ok((a - b).into()),
         }
     }
 }
@@ -215,6 +533,7 @@ impl ops::Neg for Literal {
     fn neg(self) -> Self::Output {
         match self {
             Literal::Number(n) => Ok(Literal::Number(-n)),
+            Literal::Integer(n) => checked(n.checked_neg(), "-"),
             _ => Err(LoxError::Runtime {
                 found: self.to_string(),
                 expected: "a number to negate".to_string(),
@@ -227,6 +546,7 @@ impl ops::Neg for Literal {
 impl ops::Div for Literal {
     type Output = Result<Literal, LoxError>;
 
+    /// Always a float, even for two integers. Use `~/` for integer division
     fn div(self, rhs: Self) -> Self::Output {
         let lhs = self.into_number()?;
         let rhs = rhs.into_number()?;
@@ -238,35 +558,200 @@ impl ops::Mul for Literal {
     type Output = Result<Literal, LoxError>;
 
     fn mul(self, rhs: Self) -> Self::Output {
-        let lhs = self.into_number()?;
-        let rhs = rhs.into_number()?;
-        Ok(Literal::Number(OrderedFloat(lhs * rhs)))
+        match self.numbers(rhs, "*")? {
+            Numbers::Integers(a, b) => checked(a.checked_mul(b), "*"),
+            Numbers::Floats(a, b) => Ok((a * b).into()),
+        }
     }
 }
 
-impl cmp::PartialOrd for Literal {
-    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
-        let us = self.as_number().ok()?;
-        let them = other.as_number().ok()?;
-        us.partial_cmp(them)
+impl ops::Rem for Literal {
+    type Output = Result<Literal, LoxError>;
+
+    /// Takes the sign of the left hand side, matching `~/`
+    fn rem(self, rhs: Self) -> Self::Output {
+        match self.numbers(rhs, "%")? {
+            Numbers::Integers(_, 0) => Err(LoxError::Runtime {
+                found: "division by zero".into(),
+                expected: "a non-zero divisor".into(),
+                line: None,
+            }),
+            Numbers::Integers(a, b) => checked(a.checked_rem(b), "%"),
+            Numbers::Floats(a, b) => Ok((a % b).into()),
+        }
     }
 }
 
-impl Literal {
-    pub fn into_number(self) -> Result<f64, LoxError> {
+impl ops::BitAnd for Literal {
+    type Output = Result<Literal, LoxError>;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        let (a, b) = self.integers(rhs, "&")?;
+        Ok(Literal::Integer(a & b))
+    }
+}
+
+impl ops::BitOr for Literal {
+    type Output = Result<Literal, LoxError>;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        let (a, b) = self.integers(rhs, "|")?;
+        Ok(Literal::Integer(a | b))
+    }
+}
+
+impl ops::BitXor for Literal {
+    type Output = Result<Literal, LoxError>;
+
+    fn bitxor(self, rhs: Self) -> Self::Output {
+        let (a, b) = self.integers(rhs, "^")?;
+        Ok(Literal::Integer(a ^ b))
+    }
+}
+
+impl ops::Shl for Literal {
+    type Output = Result<Literal, LoxError>;
+
+    /// Shifting bits out of the top counts as overflow
+    fn shl(self, rhs: Self) -> Self::Output {
+        let (a, b) = self.integers(rhs, "<<")?;
+        let shifted = u32::try_from(b)
+            .ok()
+            .and_then(|b| a.checked_shl(b))
+            .filter(|shifted| shifted >> b == a);
+        checked(shifted, "<<")
+    }
+}
+
+impl ops::Shr for Literal {
+    type Output = Result<Literal, LoxError>;
+
+    /// An arithmetic shift, so negative numbers stay negative
+    fn shr(self, rhs: Self) -> Self::Output {
+        let (a, b) = self.integers(rhs, ">>")?;
+        checked(u32::try_from(b).ok().and_then(|b| a.checked_shr(b)), ">>")
+    }
+}
+
+impl ops::Not for Literal {
+    type Output = Result<Literal, LoxError>;
+
+    /// `~`, bitwise not. Lox's `!` is logical not, which works on any value and is handled by the interpreter
+    fn not(self) -> Self::Output {
         match self {
-            Literal::Number(n) => Ok(*n),
+            Literal::Integer(n) => Ok(Literal::Integer(!n)),
             _ => Err(LoxError::Runtime {
                 found: self.to_string(),
-                expected: "f64".to_string(),
+                expected: "an integer for '~'".to_string(),
                 line: None,
             }),
         }
     }
+}
 
-    fn as_number(&self) -> Result<&f64, LoxError> {
+impl cmp::PartialOrd for Literal {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        match (self, other) {
+            (Literal::Integer(a), Literal::Integer(b)) => Some(a.cmp(b)),
+            (Literal::Number(a), Literal::Number(b)) => a.0.partial_cmp(&b.0),
+            (Literal::Integer(a), Literal::Number(b)) => compare_mixed(*a, **b),
+            (Literal::Number(a), Literal::Integer(b)) => compare_mixed(*b, **a).map(cmp::Ordering::reverse),
+            _ => None,
+        }
+    }
+}
+
+/// Integers and floats are equal when they hold the same value, so `1 == 1.0`
+impl cmp::PartialEq for Literal {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Literal::String(a), Literal::String(b)) => a == b,
+            (Literal::Null, Literal::Null) => true,
+            (Literal::Number(a), Literal::Number(b)) => a == b,
+            (Literal::Integer(a), Literal::Integer(b)) => a == b,
+            (Literal::Integer(i), Literal::Number(f)) | (Literal::Number(f), Literal::Integer(i)) => {
+                compare_mixed(*i, **f) == Some(cmp::Ordering::Equal)
+            }
+            (Literal::Boolean(a), Literal::Boolean(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Literal {}
+
+/// Consistent with equality, so a whole float finds the same map entry as the matching integer
+impl std::hash::Hash for Literal {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         match self {
-            Literal::Number(n) => Ok(n),
+            Literal::String(s) => (0, s).hash(state),
+            Literal::Null => 1.hash(state),
+            Literal::Boolean(b) => (2, b).hash(state),
+            Literal::Integer(i) => (3, i).hash(state),
+            Literal::Number(n) => match float_to_integer(**n) {
+                Some(i) => (3, i).hash(state),
+                None => (4, n).hash(state),
+            },
+        }
+    }
+}
+
+impl Literal {
+    /// `~/`, dividing and truncating towards zero. Always gives an integer
+    pub fn integer_div(self, rhs: Literal) -> Result<Literal, LoxError> {
+        let division_by_zero = || LoxError::Runtime {
+            found: "division by zero".into(),
+            expected: "a non-zero divisor".into(),
+            line: None,
+        };
+        match self.numbers(rhs, "~/")? {
+            Numbers::Integers(_, 0) => Err(division_by_zero()),
+            Numbers::Integers(a, b) => checked(a.checked_div(b), "~/"),
+            Numbers::Floats(_, 0.0) => Err(division_by_zero()),
+            Numbers::Floats(a, b) => checked(float_to_integer((a / b).trunc()), "~/"),
+        }
+    }
+
+    /// `**`. Integers stay integers unless the exponent is negative
+    pub fn pow(self, rhs: Literal) -> Result<Literal, LoxError> {
+        match self.numbers(rhs, "**")? {
+            Numbers::Integers(a, b) if b >= 0 => checked(u32::try_from(b).ok().and_then(|b| a.checked_pow(b)), "**"),
+            Numbers::Integers(a, b) => Ok((a as f64).powf(b as f64).into()),
+            Numbers::Floats(a, b) => Ok(a.powf(b).into()),
+        }
+    }
+
+    /// Bitwise operators only make sense on integers
+    fn integers(self, rhs: Literal, operator: &str) -> Result<(i64, i64), LoxError> {
+        match (self, rhs) {
+            (Literal::Integer(a), Literal::Integer(b)) => Ok((a, b)),
+            (lhs, rhs) => Err(LoxError::Runtime {
+                found: format!("{lhs} {operator} {rhs}"),
+                expected: format!("integer {operator} integer"),
+                line: None,
+            }),
+        }
+    }
+
+    fn is_number(&self) -> bool {
+        matches!(self, Literal::Number(_) | Literal::Integer(_))
+    }
+
+    fn numbers(self, rhs: Literal, operator: &str) -> Result<Numbers, LoxError> {
+        match (self, rhs) {
+            (Literal::Integer(a), Literal::Integer(b)) => Ok(Numbers::Integers(a, b)),
+            (lhs, rhs) => Ok(Numbers::Floats(
+                lhs.into_number().map_err(|e| operand_error(e, operator))?,
+                rhs.into_number().map_err(|e| operand_error(e, operator))?,
+            )),
+        }
+    }
+
+    /// Any number as a float, which loses precision for integers beyond 2^53
+    pub fn into_number(self) -> Result<f64, LoxError> {
+        match self {
+            Literal::Number(n) => Ok(*n),
+            Literal::Integer(n) => Ok(n as f64),
             _ => Err(LoxError::Runtime {
                 found: self.to_string(),
                 expected: "f64".to_string(),
@@ -284,6 +769,49 @@ impl Literal {
     }
 }
 
+fn operand_error(error: LoxError, operator: &str) -> LoxError {
+    match error {
+        LoxError::Runtime { found, line, .. } => LoxError::Runtime {
+            found,
+            expected: format!("number {operator} number"),
+            line,
+        },
+        other => other,
+    }
+}
+
+/// Integer arithmetic fails loudly rather than wrapping or silently turning into a float
+fn checked(result: Option<i64>, operator: &str) -> Result<Literal, LoxError> {
+    result.map(Literal::Integer).ok_or_else(|| LoxError::Runtime {
+        found: "integer overflow".into(),
+        expected: format!("the result of '{operator}' to fit in an integer"),
+        line: None,
+    })
+}
+
+/// The integer a float is exactly equal to, if there is one
+fn float_to_integer(value: f64) -> Option<i64> {
+    // 2^63 is exact as a float, unlike i64::MAX
+    let limit = 2_f64.powi(63);
+    (value.fract() == 0.0 && value >= -limit && value < limit).then_some(value as i64)
+}
+
+/// Compare without converting the integer to a float, which would round large values
+fn compare_mixed(integer: i64, float: f64) -> Option<cmp::Ordering> {
+    if float.is_nan() {
+        return None;
+    }
+    match float_to_integer(float.floor()) {
+        Some(whole) => Some(integer.cmp(&whole).then(if float.fract() > 0.0 {
+            cmp::Ordering::Less
+        } else {
+            cmp::Ordering::Equal
+        })),
+        None if float > 0.0 => Some(cmp::Ordering::Less),
+        None => Some(cmp::Ordering::Greater),
+    }
+}
+
 impl fmt::Display for Literal {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -297,6 +825,7 @@ impl fmt::Display for Literal {
                     write!(f, "{}", n)
                 }
             }
+            Literal::Integer(n) => write!(f, "{}", n),
             Literal::Boolean(b) => write!(f, "{}", b),
         }
     }
@@ -322,7 +851,7 @@ impl From<f64> for Literal {
 
 impl From<u32> for Literal {
     fn from(value: u32) -> Self {
-        Literal::Number(OrderedFloat(value as f64))
+        Literal::Integer(value.into())
     }
 }
 
@@ -337,3 +866,88 @@ impl From<bool> for Literal {
         Literal::Boolean(value)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::hash::{BuildHasher, RandomState};
+
+    use pretty_assertions::{assert_eq, assert_matches};
+
+    use super::*;
+
+    #[test]
+    fn list_index_counts_from_either_end() {
+        assert_eq!(list_index(&Object::from(0.0), 3, false).unwrap(), 0);
+        assert_eq!(list_index(&Object::from(2.0), 3, false).unwrap(), 2);
+        assert_eq!(list_index(&Object::from(-1.0), 3, false).unwrap(), 2);
+        assert_eq!(list_index(&Object::from(-3.0), 3, false).unwrap(), 0);
+    }
+
+    #[test]
+    fn list_index_rejects_out_of_range() {
+        assert_matches!(list_index(&Object::from(3.0), 3, false), Err(LoxError::Runtime { .. }));
+        assert_matches!(list_index(&Object::from(-4.0), 3, false), Err(LoxError::Runtime { .. }));
+        assert_matches!(list_index(&Object::from(0.0), 0, false), Err(LoxError::Runtime { .. }));
+    }
+
+    #[test]
+    fn list_index_allows_end_when_asked() {
+        assert_eq!(list_index(&Object::from(3.0), 3, true).unwrap(), 3);
+        assert_eq!(list_index(&Object::from(0.0), 0, true).unwrap(), 0);
+    }
+
+    #[test]
+    fn list_index_rejects_non_whole_numbers() {
+        assert_matches!(list_index(&Object::from(0.5), 3, false), Err(LoxError::Runtime { .. }));
+        assert_matches!(list_index(&Object::Literal("0".into()), 3, false), Err(LoxError::Runtime { .. }));
+    }
+
+    #[test]
+    fn integers_and_floats_compare_by_value() {
+        assert_eq!(Literal::Integer(1), Literal::from(1.0));
+        assert_ne!(Literal::Integer(1), Literal::from(1.5));
+        assert!(Literal::Integer(1) < Literal::from(1.5));
+        assert!(Literal::from(-0.5) < Literal::Integer(0));
+        // Would be equal if the integer were rounded to a float first
+        assert!(Literal::Integer(i64::MAX) < Literal::from(2_f64.powi(63)));
+        assert_ne!(Literal::Integer((1 << 53) + 1), Literal::from(2_f64.powi(53)));
+    }
+
+    #[test]
+    fn whole_floats_hash_like_integers() {
+        let state = RandomState::new();
+        assert_eq!(state.hash_one(Literal::Integer(3)), state.hash_one(Literal::from(3.0)));
+        assert_eq!(state.hash_one(Literal::Integer(0)), state.hash_one(Literal::from(-0.0)));
+    }
+
+    #[test]
+    fn integer_arithmetic_stays_integer() {
+        assert_matches!(Literal::Integer(2) + Literal::Integer(3), Ok(Literal::Integer(5)));
+        assert_matches!(Literal::Integer(7).integer_div(Literal::Integer(-2)), Ok(Literal::Integer(-3)));
+        assert_matches!(Literal::from(7.5).integer_div(Literal::Integer(2)), Ok(Literal::Integer(3)));
+        assert_eq!((Literal::Integer(2) * Literal::from(1.5)).unwrap(), Literal::from(3.0));
+        assert_matches!(Literal::Integer(1) / Literal::Integer(2), Ok(Literal::Number(_)));
+    }
+
+    #[test]
+    fn integer_overflow_is_an_error() {
+        assert_matches!(Literal::Integer(i64::MAX) + Literal::Integer(1), Err(LoxError::Runtime { .. }));
+        assert_matches!(Literal::Integer(i64::MIN) * Literal::Integer(-1), Err(LoxError::Runtime { .. }));
+        assert_matches!(-Literal::Integer(i64::MIN), Err(LoxError::Runtime { .. }));
+        assert_matches!(Literal::Integer(1).integer_div(Literal::Integer(0)), Err(LoxError::Runtime { .. }));
+    }
+
+    #[test]
+    fn shifting_out_bits_is_overflow() {
+        assert_matches!(Literal::Integer(1) << Literal::Integer(62), Ok(Literal::Integer(_)));
+        assert_matches!(Literal::Integer(1) << Literal::Integer(63), Err(LoxError::Runtime { .. }));
+        assert_matches!(Literal::Integer(-1) << Literal::Integer(63), Ok(Literal::Integer(i64::MIN)));
+        assert_matches!(Literal::Integer(1) << Literal::Integer(-1), Err(LoxError::Runtime { .. }));
+    }
+
+    #[test]
+    fn bitwise_operators_need_integers() {
+        assert_matches!(Literal::from(1.0) & Literal::Integer(1), Err(LoxError::Runtime { .. }));
+        assert_matches!(!Literal::from("1"), Err(LoxError::Runtime { .. }));
+    }
+}
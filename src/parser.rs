@@ -2,8 +2,14 @@ use tracing::trace;
 
 use super::{LoxError, Result};
 use crate::{
-    expr::{Assign, Binary, Call, Expr, Get, Grouping, Literal, Logical, Set, This, Unary, Variable},
-    stmt::{Block, Class, Expression, Function, If, Print, Return, Stmt, Var, While},
+    expr::{
+        Assign, Binary, Call, CompoundAssign, Conditional, Expr, Get, Grouping, Index, Lambda, List, Literal, Logical, Map, Set, SetIndex,
+        Stringify, Super, This, Unary, Variable,
+    },
+    stmt::{
+        Block, Break, Catch, Class, Continue, Expression, ForIn, Function, If, Import, Match, MatchArm, Pattern, Print, Return, Stmt,
+        Throw, Try, Var, While, Yield,
+    },
     token::Token,
     token_type::TokenType,
 };
@@ -41,12 +47,20 @@ impl Parser {
         if self.match_advance(&[TokenType::Class]) {
             return self.class_declaration();
         }
-        if self.match_advance(&[TokenType::Fun]) {
+        // `fun (...) {}` without a name is a lambda, which is parsed as an expression
+        if self.check(&TokenType::Fun) && self.check_next(&TokenType::Identifier) {
+            self.advance();
             return self.function_stmt("function");
         }
         if self.match_advance(&[TokenType::Var]) {
             return self.var_declaration();
         }
+        if self.match_advance(&[TokenType::Const]) {
+            return self.const_declaration();
+        }
+        if self.match_advance(&[TokenType::Import]) {
+            return self.import_declaration();
+        }
         self.statement()
     }
 
@@ -62,36 +76,104 @@ impl Parser {
         Ok(Var::stmt(name, initializer))
     }
 
+    /// `const NAME = value;`, which has to have a value since it can't be assigned one later
+    fn const_declaration(&mut self) -> Result<Stmt> {
+        let name = self.consume(TokenType::Identifier, "Expect constant name.")?;
+        self.consume(TokenType::Equal, "Expect '=' after constant name, constants must be initialized.")?;
+        let initializer = self.expression()?;
+        self.consume(TokenType::Semicolon, "Expect ';' after constant declaration")?;
+
+        Ok(Var::constant(name, initializer))
+    }
+
+    /// `import "lib/util.lox";` or `import lib.util;` binds the module as `util`, while
+    /// `import lib.util (greet, parse);` binds only the selected names
+    fn import_declaration(&mut self) -> Result<Stmt> {
+        let keyword = self.previous();
+        let (path, name) = if self.match_advance(&[TokenType::String]) {
+            let path = self.previous().literal.to_string();
+            let name = std::path::Path::new(&path)
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            (path, name)
+        } else {
+            let mut segments = vec![
+                self.consume(TokenType::Identifier, "Expect module path or name after 'import'.")?
+                    .lexeme,
+            ];
+            while self.match_advance(&[TokenType::Dot]) {
+                segments.push(self.consume(TokenType::Identifier, "Expect module name after '.'.")?.lexeme);
+            }
+            let name = segments.last().cloned().unwrap_or_default();
+            (format!("{}.lox", segments.join("/")), name)
+        };
+
+        let mut names = Vec::new();
+        if self.match_advance(&[TokenType::LeftParen]) {
+            loop {
+                names.push(self.consume(TokenType::Identifier, "Expect name to import.")?);
+                if !self.match_advance(&[TokenType::Comma]) {
+                    break;
+                }
+            }
+            self.consume(TokenType::RightParen, "Expect ')' after imported names.")?;
+        }
+        self.consume(TokenType::Semicolon, "Expect ';' after import.")?;
+
+        Ok(Import::stmt(keyword, path, name, names))
+    }
+
     fn class_declaration(&mut self) -> Result<Stmt> {
         let name = self.consume(TokenType::Identifier, "Expect class name.")?;
         trace!(?name, ">> class_declaration()");
+        let superclass = if self.match_advance(&[TokenType::Less]) {
+            let superclass = self.consume(TokenType::Identifier, "Expect superclass name.")?;
+            Some(Variable::new(superclass))
+        } else {
+            None
+        };
         self.consume(TokenType::LeftBrace, "Expect '{' before class body.")?;
 
         let mut methods = Vec::new();
+        let mut class_methods = Vec::new();
         while !self.check(&TokenType::RightBrace) && !self.is_at_end() {
-            let Ok(Stmt::Function(func)) = self.function_stmt("method") else {
-                return Err(LoxError::Internal {
-                    message: "Did not find method".into(),
-                });
+            let is_class_method = self.match_advance(&[TokenType::Class]);
+            let func = if self.match_advance(&[TokenType::PrivateIdentifier]) {
+                self.function(self.previous(), "method")?
+            } else {
+                let Ok(Stmt::Function(func)) = self.function_stmt("method") else {
+                    return Err(LoxError::Internal {
+                        message: "Did not find method".into(),
+                    });
+                };
+                func
             };
-            methods.push(func);
+            if is_class_method {
+                class_methods.push(func);
+            } else {
+                methods.push(func);
+            }
         }
 
         self.consume(TokenType::RightBrace, "Expect '}' after class body.")?;
 
-        trace!(?methods, "<< class_declaration()");
-        Ok(Class::stmt(name, methods))
+        trace!(?methods, ?class_methods, "<< class_declaration()");
+        Ok(Class::stmt(name, superclass, methods, class_methods))
     }
 }
 
 // Statements
 impl Parser {
     fn statement(&mut self) -> Result<Stmt> {
+        if self.check(&TokenType::Identifier) && self.check_next(&TokenType::Colon) {
+            return self.labelled_statement();
+        }
         if self.match_advance(&[TokenType::Print]) {
             return self.print_statement();
         }
         if self.match_advance(&[TokenType::While]) {
-            return self.while_statement();
+            return self.while_statement(None);
         }
         if self.match_advance(&[TokenType::LeftBrace]) {
             return Ok(Block::stmt(self.block_stmt()?));
@@ -100,27 +182,73 @@ impl Parser {
             return self.if_statement();
         }
         if self.match_advance(&[TokenType::For]) {
-            return self.for_statement();
+            return self.for_statement(None);
         }
         if self.match_advance(&[TokenType::Return]) {
             return self.return_stmt();
         }
+        if self.match_advance(&[TokenType::Yield]) {
+            return self.yield_stmt();
+        }
+        if self.match_advance(&[TokenType::Break]) {
+            let (keyword, label) = self.loop_jump("break")?;
+            return Ok(Break::stmt(keyword, label));
+        }
+        if self.match_advance(&[TokenType::Continue]) {
+            let (keyword, label) = self.loop_jump("continue")?;
+            return Ok(Continue::stmt(keyword, label));
+        }
+        if self.match_advance(&[TokenType::Throw]) {
+            return self.throw_stmt();
+        }
+        if self.match_advance(&[TokenType::Match]) {
+            return self.match_stmt();
+        }
+        if self.match_advance(&[TokenType::Try]) {
+            return self.try_stmt();
+        }
         self.expression_stmt()
     }
 
+    /// `outer: while (...) ...`, the label can be targeted by `break` or `continue` in nested loops
+    fn labelled_statement(&mut self) -> Result<Stmt> {
+        let label = self.advance();
+        self.consume(TokenType::Colon, "Expect ':' after label.")?;
+        if self.match_advance(&[TokenType::While]) {
+            return self.while_statement(Some(label));
+        }
+        if self.match_advance(&[TokenType::For]) {
+            return self.for_statement(Some(label));
+        }
+        Err(error(self.peek(), "Expect a loop after label."))
+    }
+
+    /// The rest of a `break` or `continue`, with an optional label naming the loop
+    fn loop_jump(&mut self, kind: &str) -> Result<(Token, Option<Token>)> {
+        let keyword = self.previous();
+        let label = if self.check(&TokenType::Identifier) {
+            Some(self.advance())
+        } else {
+            None
+        };
+        self.consume(TokenType::Semicolon, format!("Expect ';' after '{kind}'.").as_str())?;
+        Ok((keyword, label))
+    }
+
     fn print_statement(&mut self) -> Result<Stmt> {
+        let keyword = self.previous();
         let value = self.expression()?;
         self.consume(TokenType::Semicolon, "Expect ';' after value")?;
-        Ok(Print::stmt(value))
+        Ok(Print::stmt(keyword, value))
     }
 
-    fn while_statement(&mut self) -> Result<Stmt> {
+    fn while_statement(&mut self, label: Option<Token>) -> Result<Stmt> {
         self.consume(TokenType::LeftParen, "Expect '(' after 'while'.")?;
         let condition = self.expression()?;
         self.consume(TokenType::RightParen, "Expect ')' after condition.")?;
         let body = self.statement()?;
 
-        Ok(While::stmt(condition, body))
+        Ok(While::stmt(condition, body, None, label))
     }
 
     fn if_statement(&mut self) -> Result<Stmt> {
@@ -139,11 +267,23 @@ impl Parser {
     }
 
     /// De-sugar a for statement into a while statement
-    fn for_statement(&mut self) -> Result<Stmt> {
+    fn for_statement(&mut self, label: Option<Token>) -> Result<Stmt> {
         // for (var i = 0; i < 10; i = i + 1) {
         // print i;
         // }
         self.consume(TokenType::LeftParen, "Expect '(' after 'for'.")?;
+        // for (var x in iterable), where the `var` is optional
+        if self.check(&TokenType::Var) && self.check_next(&TokenType::Identifier) {
+            if let Some(token) = self.tokens.get(self.current + 2)
+                && token.typ == TokenType::In
+            {
+                self.advance();
+                return self.for_in_statement(label);
+            }
+        } else if self.check(&TokenType::Identifier) && self.check_next(&TokenType::In) {
+            return self.for_in_statement(label);
+        }
+
         // `var i = 0;`, could also be empty, or just an expression which
         // we'd treat as a statement to keep things tidy
         let initializer = match self.peek().typ {
@@ -175,27 +315,19 @@ impl Parser {
         // `{ print i; }`
         let mut body = self.statement()?;
 
-        // Now, build out the while statement, working backwards
-        if let Some(incr) = increment {
-            // {
-            //   { print i; }
-            //   i = i + 1;
-            // }
-            body = Block::stmt(vec![body, Expression::stmt(incr)]);
-        }
+        // Now, build out the while statement, working backwards. The increment is kept
+        // separate from the body so that `continue` still runs it
         // while (i < 10) {
         //   { print i; }
-        //   i = i + 1;
-        // }
-        body = While::stmt(condition, body);
+        // } then i = i + 1;
+        body = While::stmt(condition, body, increment, label);
 
         // {
         //   // scope `var` to just this block
         //   var i = 0;
         //   while (i < 10) {
         //     { print i; }
-        //     i = i + 1;
-        //   }
+        //   } then i = i + 1;
         // }
         if let Some(init) = initializer {
             body = Block::stmt(vec![init, body]);
@@ -206,6 +338,19 @@ impl Parser {
         Ok(body)
     }
 
+    /// The rest of `for (var x in iterable) body`, from the `x`
+    fn for_in_statement(&mut self, label: Option<Token>) -> Result<Stmt> {
+        let name = self.consume(TokenType::Identifier, "Expect loop variable name.")?;
+        self.consume(TokenType::In, "Expect 'in' after loop variable.")?;
+        let iterable = self.expression()?;
+        self.consume(TokenType::RightParen, "Expect ')' after for clauses.")?;
+        let body = self.statement()?;
+
+        let body = ForIn::stmt(name, iterable, body, label);
+        trace!(?body, "Created ForIn loop");
+        Ok(body)
+    }
+
     fn expression_stmt(&mut self) -> Result<Stmt> {
         let expr = self.expression()?;
         self.consume(TokenType::Semicolon, "Expect ';' after expression")?;
@@ -225,14 +370,44 @@ impl Parser {
 
     fn function_stmt(&mut self, kind: &str) -> Result<Stmt> {
         let name = self.consume(TokenType::Identifier, format!("Expect {kind} name.").as_str())?;
+        let function = self.function(name, kind)?;
+        Ok(Stmt::Function(function))
+    }
+
+    /// Parameters and body of a function, after its name
+    fn function(&mut self, name: Token, kind: &str) -> Result<Function> {
+        // `area { ... }` in a class body is a getter
+        if kind == "method" && self.match_advance(&[TokenType::LeftBrace]) {
+            let body = self.block_stmt()?;
+            return Ok(Function {
+                getter: true,
+                ..Function::new(name, Vec::new(), body)
+            });
+        }
         self.consume(TokenType::LeftParen, format!("Expect '(' after {kind} name.").as_str())?;
         let mut parameters = Vec::new();
+        let mut defaults = Vec::new();
+        let mut rest = None;
         if !self.check(&TokenType::RightParen) {
             loop {
                 if parameters.len() >= 255 {
                     error(self.peek(), "Can't have more than 255 parameters.");
                 }
-                parameters.push(self.consume(TokenType::Identifier, "Expext parameter name")?);
+                // `...rest` has to come last
+                if self.match_advance(&[TokenType::DotDotDot]) {
+                    rest = Some(self.consume(TokenType::Identifier, "Expect rest parameter name after '...'.")?);
+                    break;
+                }
+                let parameter = self.consume(TokenType::Identifier, "Expext parameter name")?;
+                let default = match self.match_advance(&[TokenType::Equal]) {
+                    true => Some(self.expression()?),
+                    false => None,
+                };
+                if default.is_none() && defaults.iter().any(Option::is_some) {
+                    return Err(error(&parameter, "Parameters without defaults can't follow ones with them."));
+                }
+                parameters.push(parameter);
+                defaults.push(default);
                 if !self.match_advance(&[TokenType::Comma]) {
                     break;
                 }
@@ -241,7 +416,11 @@ impl Parser {
         self.consume(TokenType::RightParen, "Expect ')' after parameters.")?;
         self.consume(TokenType::LeftBrace, format!("Expect '{{' before {kind} body.").as_str())?;
         let body = self.block_stmt()?;
-        Ok(Function::stmt(name, parameters, body))
+        Ok(Function {
+            defaults,
+            rest,
+            ..Function::new(name, parameters, body)
+        })
     }
 
     fn return_stmt(&mut self) -> Result<Stmt> {
@@ -256,6 +435,182 @@ impl Parser {
 
         Ok(Return::stmt(keyword, value))
     }
+
+    fn yield_stmt(&mut self) -> Result<Stmt> {
+        let keyword = self.previous();
+        let value = match self.check(&TokenType::Semicolon) {
+            true => None,
+            false => Some(self.expression()?),
+        };
+        self.consume(TokenType::Semicolon, "Expect ';' after yielded value.")?;
+        Ok(Yield::stmt(keyword, value))
+    }
+
+    /// `match (value) { pattern => expression, pattern => { ... } }`
+    fn match_stmt(&mut self) -> Result<Stmt> {
+        let keyword = self.previous();
+        self.consume(TokenType::LeftParen, "Expect '(' after 'match'.")?;
+        let value = self.expression()?;
+        self.consume(TokenType::RightParen, "Expect ')' after match value.")?;
+        self.consume(TokenType::LeftBrace, "Expect '{' before match arms.")?;
+
+        let mut arms = Vec::new();
+        while !self.check(&TokenType::RightBrace) && !self.is_at_end() {
+            let pattern = self.pattern()?;
+            self.consume(TokenType::FatArrow, "Expect '=>' after pattern.")?;
+            let body = self.match_arm_body()?;
+            arms.push(MatchArm { pattern, body });
+        }
+        self.consume(TokenType::RightBrace, "Expect '}' after match arms.")?;
+
+        Ok(Match::stmt(keyword, value, arms))
+    }
+
+    /// Usually `pattern => expression,`, where the comma can be left off the last arm. Anything
+    /// that starts a statement, like a block or `return`, is parsed as that statement instead
+    fn match_arm_body(&mut self) -> Result<Stmt> {
+        let starts_statement = [
+            TokenType::LeftBrace,
+            TokenType::Print,
+            TokenType::While,
+            TokenType::If,
+            TokenType::For,
+            TokenType::Return,
+            TokenType::Yield,
+            TokenType::Break,
+            TokenType::Continue,
+            TokenType::Throw,
+            TokenType::Match,
+            TokenType::Try,
+        ];
+        if starts_statement.iter().any(|typ| self.check(typ)) || (self.check(&TokenType::Identifier) && self.check_next(&TokenType::Colon))
+        {
+            let body = self.statement()?;
+            self.match_advance(&[TokenType::Comma]);
+            return Ok(body);
+        }
+
+        let expression = self.expression()?;
+        // `;` is accepted too, so an arm reads the same as an expression statement
+        if !self.match_advance(&[TokenType::Comma, TokenType::Semicolon]) && !self.check(&TokenType::RightBrace) {
+            return Err(error(self.peek(), "Expect ',' after match arm."));
+        }
+        Ok(Expression::stmt(expression))
+    }
+
+    fn pattern(&mut self) -> Result<Pattern> {
+        let first = self.single_pattern()?;
+        if !self.check(&TokenType::Pipe) {
+            return Ok(first);
+        }
+        let mut alternatives = vec![first];
+        while self.match_advance(&[TokenType::Pipe]) {
+            alternatives.push(self.single_pattern()?);
+        }
+        Ok(Pattern::Alternatives(alternatives))
+    }
+
+    fn single_pattern(&mut self) -> Result<Pattern> {
+        if self.match_advance(&[TokenType::Identifier]) {
+            let name = self.previous();
+            if name.lexeme == "_" {
+                return Ok(Pattern::Wildcard);
+            }
+            if self.match_advance(&[TokenType::LeftParen]) {
+                return self.class_pattern(name);
+            }
+            return Ok(Pattern::Binding(name));
+        }
+        if self.match_advance(&[TokenType::Nil]) {
+            return Ok(Pattern::Literal(().into()));
+        }
+        if self.match_advance(&[TokenType::True, TokenType::False]) {
+            return Ok(Pattern::Literal((self.previous().typ == TokenType::True).into()));
+        }
+        if self.match_advance(&[TokenType::String]) {
+            return Ok(Pattern::Literal(self.previous().literal));
+        }
+
+        let start = self.pattern_number()?;
+        if self.match_advance(&[TokenType::DotDot, TokenType::DotDotEqual]) {
+            let inclusive = self.previous().typ == TokenType::DotDotEqual;
+            let end = self.pattern_number()?;
+            return Ok(Pattern::Range { start, end, inclusive });
+        }
+        Ok(Pattern::Literal(start))
+    }
+
+    /// A number in a pattern, which can be negative
+    fn pattern_number(&mut self) -> Result<crate::object::Literal> {
+        let negative = self.match_advance(&[TokenType::Minus]);
+        let number = self.consume(TokenType::Number, "Expect pattern.")?;
+        if negative {
+            return (-number.literal.clone()).map_err(|_| error(&number, "Number is out of range."));
+        }
+        Ok(number.literal)
+    }
+
+    /// The fields of `Name(field, field: pattern)`, after the `(`
+    fn class_pattern(&mut self, name: Token) -> Result<Pattern> {
+        let mut fields = Vec::new();
+        if !self.check(&TokenType::RightParen) {
+            loop {
+                let field = self.consume(TokenType::Identifier, "Expect field name in class pattern.")?;
+                let pattern = if self.match_advance(&[TokenType::Colon]) {
+                    self.pattern()?
+                } else {
+                    Pattern::Binding(field.clone())
+                };
+                fields.push((field, pattern));
+                if !self.match_advance(&[TokenType::Comma]) {
+                    break;
+                }
+            }
+        }
+        self.consume(TokenType::RightParen, "Expect ')' after class pattern fields.")?;
+        Ok(Pattern::Class {
+            name: Variable::new(name),
+            fields,
+        })
+    }
+
+    fn throw_stmt(&mut self) -> Result<Stmt> {
+        let keyword = self.previous();
+        let value = self.expression()?;
+        self.consume(TokenType::Semicolon, "Expect ';' after thrown value.")?;
+        Ok(Throw::stmt(keyword, value))
+    }
+
+    /// `try { ... } catch (e) { ... } finally { ... }`, where at least one of `catch` or `finally` is required
+    fn try_stmt(&mut self) -> Result<Stmt> {
+        self.consume(TokenType::LeftBrace, "Expect '{' after 'try'.")?;
+        let body = self.block_stmt()?;
+
+        let catch = if self.match_advance(&[TokenType::Catch]) {
+            self.consume(TokenType::LeftParen, "Expect '(' after 'catch'.")?;
+            let name = self.consume(TokenType::Identifier, "Expect error variable name.")?;
+            self.consume(TokenType::RightParen, "Expect ')' after error variable.")?;
+            self.consume(TokenType::LeftBrace, "Expect '{' before catch body.")?;
+            Some(Catch {
+                name,
+                body: self.block_stmt()?,
+            })
+        } else {
+            None
+        };
+
+        let finally = if self.match_advance(&[TokenType::Finally]) {
+            self.consume(TokenType::LeftBrace, "Expect '{' after 'finally'.")?;
+            Some(self.block_stmt()?)
+        } else {
+            None
+        };
+
+        if catch.is_none() && finally.is_none() {
+            return Err(error(self.peek(), "Expect 'catch' or 'finally' after try block."));
+        }
+        Ok(Try::stmt(body, catch, finally))
+    }
 }
 
 // Expressions
@@ -265,7 +620,7 @@ impl Parser {
     }
 
     fn assignment(&mut self) -> Result<Expr> {
-        let expr = self.or()?;
+        let expr = self.conditional()?;
 
         trace!(?expr, ">> assignment()");
         if self.match_advance(&[TokenType::Equal]) {
@@ -284,12 +639,55 @@ impl Parser {
                 return Ok(Set::expr(*get.object, name, value));
             }
 
+            if let Expr::Index(index) = expr {
+                trace!(?index, ?value, "Index expr");
+                return Ok(SetIndex::expr(*index.object, index.bracket, *index.index, value));
+            }
+
             Err(error(&equals, "Invalid assignment target."))
+        } else if self.match_advance(&[
+            TokenType::PlusEqual,
+            TokenType::MinusEqual,
+            TokenType::StarEqual,
+            TokenType::SlashEqual,
+        ]) {
+            let operator = self.previous();
+            let value = self.assignment()?;
+            self.compound_assignment(expr, &operator, value, false)
         } else {
             Ok(expr)
         }
     }
 
+    /// `+=` and friends, as well as `++` and `--`, which add or subtract one. The operator token is
+    /// turned into the plain arithmetic operator it applies
+    fn compound_assignment(&self, target: Expr, operator: &Token, value: Expr, postfix: bool) -> Result<Expr> {
+        if !matches!(target, Expr::Variable(_) | Expr::Get(_) | Expr::Index(_)) {
+            return Err(error(operator, "Invalid assignment target."));
+        }
+        let arithmetic = match operator.typ {
+            TokenType::PlusEqual | TokenType::PlusPlus => TokenType::Plus,
+            TokenType::MinusEqual | TokenType::MinusMinus => TokenType::Minus,
+            TokenType::StarEqual => TokenType::Star,
+            _ => TokenType::Slash,
+        };
+        let lexeme = &operator.lexeme[..1];
+        let arithmetic = Token::new(arithmetic, lexeme, operator.literal.clone(), operator.line);
+        Ok(CompoundAssign::expr(target, arithmetic, value, postfix))
+    }
+
+    /// `a ? b : c ? d : e` groups as `a ? b : (c ? d : e)`
+    fn conditional(&mut self) -> Result<Expr> {
+        let condition = self.or()?;
+        if self.match_advance(&[TokenType::Question]) {
+            let then_branch = self.expression()?;
+            self.consume(TokenType::Colon, "Expect ':' after then branch of conditional expression.")?;
+            let else_branch = self.conditional()?;
+            return Ok(Conditional::expr(condition, then_branch, else_branch));
+        }
+        Ok(condition)
+    }
+
     fn or(&mut self) -> Result<Expr> {
         let mut expr = self.and()?;
 
@@ -327,8 +725,53 @@ impl Parser {
     }
 
     fn comparison(&mut self) -> Result<Expr> {
-        let mut expr = self.term()?;
+        let mut expr = self.bit_or()?;
         while self.match_advance(&[TokenType::Greater, TokenType::GreaterEqual, TokenType::Less, TokenType::LessEqual]) {
+            let operator = self.previous();
+            let right = self.bit_or()?;
+            expr = Binary::expr(expr, operator, right);
+        }
+
+        Ok(expr)
+    }
+
+    // Bitwise operators bind tighter than comparisons, so `x & 1 == 0` does what it looks like
+    fn bit_or(&mut self) -> Result<Expr> {
+        let mut expr = self.bit_xor()?;
+        while self.match_advance(&[TokenType::Pipe]) {
+            let operator = self.previous();
+            let right = self.bit_xor()?;
+            expr = Binary::expr(expr, operator, right);
+        }
+
+        Ok(expr)
+    }
+
+    fn bit_xor(&mut self) -> Result<Expr> {
+        let mut expr = self.bit_and()?;
+        while self.match_advance(&[TokenType::Caret]) {
+            let operator = self.previous();
+            let right = self.bit_and()?;
+            expr = Binary::expr(expr, operator, right);
+        }
+
+        Ok(expr)
+    }
+
+    fn bit_and(&mut self) -> Result<Expr> {
+        let mut expr = self.shift()?;
+        while self.match_advance(&[TokenType::Ampersand]) {
+            let operator = self.previous();
+            let right = self.shift()?;
+            expr = Binary::expr(expr, operator, right);
+        }
+
+        Ok(expr)
+    }
+
+    fn shift(&mut self) -> Result<Expr> {
+        let mut expr = self.term()?;
+        while self.match_advance(&[TokenType::LessLess, TokenType::GreaterGreater]) {
             let operator = self.previous();
             let right = self.term()?;
             expr = Binary::expr(expr, operator, right);
@@ -350,7 +793,7 @@ impl Parser {
 
     fn factor(&mut self) -> Result<Expr> {
         let mut expr = self.unary()?;
-        while self.match_advance(&[TokenType::Slash, TokenType::Star]) {
+        while self.match_advance(&[TokenType::Slash, TokenType::Star, TokenType::TildeSlash, TokenType::Percent]) {
             let operator = self.previous();
             let right = self.unary()?;
             expr = Binary::expr(expr, operator, right);
@@ -360,12 +803,39 @@ impl Parser {
     }
 
     fn unary(&mut self) -> Result<Expr> {
-        if self.match_advance(&[TokenType::Bang, TokenType::Minus]) {
+        if self.match_advance(&[TokenType::Bang, TokenType::Minus, TokenType::Tilde]) {
             let operator = self.previous();
             let right = self.unary()?;
             return Ok(Unary::expr(operator, right));
         }
-        self.call()
+        if self.match_advance(&[TokenType::PlusPlus, TokenType::MinusMinus]) {
+            let operator = self.previous();
+            let target = self.unary()?;
+            return self.compound_assignment(target, &operator, Literal::expr(1.into()), false);
+        }
+        self.power()
+    }
+
+    /// `**` binds tighter than unary operators on its left, so `-2 ** 2` is `-(2 ** 2)`, and is
+    /// right-associative, so `2 ** 3 ** 2` is `2 ** (3 ** 2)`
+    fn power(&mut self) -> Result<Expr> {
+        let expr = self.postfix()?;
+        if self.match_advance(&[TokenType::StarStar]) {
+            let operator = self.previous();
+            let right = self.unary()?;
+            return Ok(Binary::expr(expr, operator, right));
+        }
+        Ok(expr)
+    }
+
+    /// `x++` and `x--`
+    fn postfix(&mut self) -> Result<Expr> {
+        let expr = self.call()?;
+        if self.match_advance(&[TokenType::PlusPlus, TokenType::MinusMinus]) {
+            let operator = self.previous();
+            return self.compound_assignment(expr, &operator, Literal::expr(1.into()), true);
+        }
+        Ok(expr)
     }
 
     fn call(&mut self) -> Result<Expr> {
@@ -375,8 +845,15 @@ impl Parser {
             if self.match_advance(&[TokenType::LeftParen]) {
                 expr = self.finish_call(expr)?;
             } else if self.match_advance(&[TokenType::Dot]) {
-                let name = self.consume(TokenType::Identifier, "Expect property name after '.'")?;
+                let name = match self.match_advance(&[TokenType::PrivateIdentifier]) {
+                    true => self.previous(),
+                    false => self.consume(TokenType::Identifier, "Expect property name after '.'")?,
+                };
                 expr = Get::expr(expr, name);
+            } else if self.match_advance(&[TokenType::LeftBracket]) {
+                let index = self.expression()?;
+                let bracket = self.consume(TokenType::RightBracket, "Expect ']' after index.")?;
+                expr = Index::expr(expr, bracket, index);
             } else {
                 break;
             }
@@ -404,12 +881,74 @@ impl Parser {
         Ok(Call::expr(callee, paren, arguments))
     }
 
+    /// `[1, 2, 3]`, the opening bracket has already been consumed. A trailing comma is allowed
+    fn list(&mut self) -> Result<Expr> {
+        let mut elements = Vec::new();
+        while !self.check(&TokenType::RightBracket) {
+            elements.push(self.expression()?);
+            if !self.match_advance(&[TokenType::Comma]) {
+                break;
+            }
+        }
+        let bracket = self.consume(TokenType::RightBracket, "Expect ']' after list elements.")?;
+
+        Ok(List::expr(bracket, elements))
+    }
+
+    /// `{"a": 1, "b": 2}`, the opening brace has already been consumed. A trailing comma is allowed
+    fn map(&mut self) -> Result<Expr> {
+        let mut entries = Vec::new();
+        while !self.check(&TokenType::RightBrace) {
+            let key = self.expression()?;
+            self.consume(TokenType::Colon, "Expect ':' after map key.")?;
+            let value = self.expression()?;
+            entries.push((key, value));
+            if !self.match_advance(&[TokenType::Comma]) {
+                break;
+            }
+        }
+        let brace = self.consume(TokenType::RightBrace, "Expect '}' after map entries.")?;
+
+        Ok(Map::expr(brace, entries))
+    }
+
+    /// `"Hello ${name}!"` becomes `"Hello " + str(name) + "!"`. The scanner produces an `Interpolation`
+    /// token for each fragment followed by `${`, then the embedded expression's tokens, and finally a
+    /// `String` token for the rest of the string
+    fn interpolation(&mut self) -> Result<Expr> {
+        let mut expr = Literal::expr(self.previous().literal);
+        loop {
+            let plus = Token::new(TokenType::Plus, "+", ().into(), self.previous().line);
+            let embedded = Stringify::expr(self.previous(), self.expression()?);
+            expr = Binary::expr(expr, plus.clone(), embedded);
+
+            let fragment = if self.match_advance(&[TokenType::Interpolation]) {
+                self.previous()
+            } else {
+                self.consume(TokenType::String, "Expect '}' after interpolated expression.")?
+            };
+            if fragment.literal != "".into() {
+                expr = Binary::expr(expr, plus, Literal::expr(fragment.literal.clone()));
+            }
+            if fragment.typ == TokenType::String {
+                return Ok(expr);
+            }
+        }
+    }
+
     fn primary(&mut self) -> Result<Expr> {
         match self.advance().typ {
             TokenType::False => Ok(Literal::expr(false.into())),
             TokenType::True => Ok(Literal::expr(true.into())),
             TokenType::Nil => Ok(Literal::expr(().into())),
             TokenType::Number | TokenType::String => Ok(Literal::expr(self.previous().literal)),
+            TokenType::Interpolation => self.interpolation(),
+            TokenType::Super => {
+                let keyword = self.previous();
+                self.consume(TokenType::Dot, "Expect '.' after 'super'.")?;
+                let method = self.consume(TokenType::Identifier, "Expect superclass method name.")?;
+                Ok(Super::expr(keyword, method))
+            }
             TokenType::This => {
                 trace!(this = ?self.previous(), "Parsed this");
                 Ok(This::expr(self.previous()))
@@ -420,6 +959,14 @@ impl Parser {
                 self.consume(TokenType::RightParen, "Expected ')' after expression.")?;
                 Ok(Grouping::expr(expr))
             }
+            TokenType::Fun => {
+                // Lambdas get a stand-in name, which also records where they were defined
+                let keyword = self.previous();
+                let name = Token::new(TokenType::Identifier, "anonymous", ().into(), keyword.line);
+                Ok(Lambda::expr(self.function(name, "lambda")?))
+            }
+            TokenType::LeftBracket => self.list(),
+            TokenType::LeftBrace => self.map(),
             _ => Err(error(&self.previous(), "Expected an expression")),
         }
     }
@@ -462,6 +1009,14 @@ impl Parser {
         &self.tokens[self.current]
     }
 
+    /// Like `check`, but for the token after the next one
+    fn check_next(&self, typ: &TokenType) -> bool {
+        match self.tokens.get(self.current + 1) {
+            Some(token) => &token.typ == typ,
+            None => false,
+        }
+    }
+
     /// Returns the next token and advances over it (if not at the end)
     fn advance(&mut self) -> Token {
         if !self.is_at_end() {
@@ -485,11 +1040,17 @@ impl Parser {
                 TokenType::Class
                 | TokenType::Fun
                 | TokenType::Var
+                | TokenType::Const
+                | TokenType::Import
                 | TokenType::For
                 | TokenType::If
                 | TokenType::While
                 | TokenType::Print
-                | TokenType::Return => {
+                | TokenType::Return
+                | TokenType::Yield
+                | TokenType::Match
+                | TokenType::Throw
+                | TokenType::Try => {
                     return;
                 }
                 _ => {
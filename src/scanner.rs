@@ -1,14 +1,18 @@
 use ordered_float::OrderedFloat;
 use tracing::{error, instrument};
+use unicode_xid::UnicodeXID;
 
 use crate::{LoxError, Result, object::Literal, token::Token, token_type::TokenType};
 
 pub struct Scanner {
     source: String,
     tokens: Vec<Token>,
+    /// Byte offsets into `source`, always on a char boundary
     current: usize,
     start: usize,
     line: usize,
+    /// One entry per `${` we're currently inside of, counting the unclosed `{`s within it
+    interpolations: Vec<usize>,
 }
 
 impl Scanner {
@@ -19,6 +23,7 @@ impl Scanner {
             current: 0,
             start: 0,
             line: 1,
+            interpolations: Vec::new(),
         }
     }
 
@@ -29,16 +34,15 @@ impl Scanner {
 
         while self.current < eof {
             self.start = self.current;
-            if self
-                .scan_token()
-                .map_err(|error| error!(?error, "Error while scanning"))
-                .is_err()
-            {
+            if self.scan_token().map_err(|error| error!(?error, "Error while scanning")).is_err() {
                 had_error = true;
             }
         }
-        self.tokens
-            .push(Token::new(TokenType::Eof, "", Literal::Null, self.line));
+        if !self.interpolations.is_empty() {
+            error!(line = self.line, "Unterminated string interpolation");
+            had_error = true;
+        }
+        self.tokens.push(Token::new(TokenType::Eof, "", Literal::Null, self.line));
 
         match had_error {
             false => Ok(self.tokens),
@@ -50,28 +54,123 @@ impl Scanner {
     fn scan_token(&mut self) -> Result<()> {
         let c = self.advance();
         let mut if_equals_else = |is_equal: TokenType, not_equal: TokenType| {
-            let token_type = if self.advance_if_is('=') {
-                is_equal
-            } else {
-                not_equal
-            };
+            let token_type = if self.advance_if_is('=') { is_equal } else { not_equal };
             self.add_token(token_type);
         };
         match c {
             '(' => self.add_token(TokenType::LeftParen),
             ')' => self.add_token(TokenType::RightParen),
-            '{' => self.add_token(TokenType::LeftBrace),
-            '}' => self.add_token(TokenType::RightBrace),
+            '{' => {
+                if let Some(depth) = self.interpolations.last_mut() {
+                    *depth += 1;
+                }
+                self.add_token(TokenType::LeftBrace)
+            }
+            '}' => match self.interpolations.last_mut() {
+                // Closes a `${`, so pick the string back up where it left off
+                Some(0) => {
+                    self.interpolations.pop();
+                    self.string()?
+                }
+                Some(depth) => {
+                    *depth -= 1;
+                    self.add_token(TokenType::RightBrace)
+                }
+                None => self.add_token(TokenType::RightBrace),
+            },
+            '[' => self.add_token(TokenType::LeftBracket),
+            ']' => self.add_token(TokenType::RightBracket),
+            ':' => self.add_token(TokenType::Colon),
+            '?' => self.add_token(TokenType::Question),
             ',' => self.add_token(TokenType::Comma),
-            '.' => self.add_token(TokenType::Dot),
-            '-' => self.add_token(TokenType::Minus),
-            '+' => self.add_token(TokenType::Plus),
+            '.' => {
+                let token_type = if self.advance_if_is('.') {
+                    if self.advance_if_is('.') {
+                        TokenType::DotDotDot
+                    } else if self.advance_if_is('=') {
+                        TokenType::DotDotEqual
+                    } else {
+                        TokenType::DotDot
+                    }
+                } else {
+                    TokenType::Dot
+                };
+                self.add_token(token_type)
+            }
+            '-' => {
+                let token_type = if self.advance_if_is('-') {
+                    TokenType::MinusMinus
+                } else if self.advance_if_is('=') {
+                    TokenType::MinusEqual
+                } else {
+                    TokenType::Minus
+                };
+                self.add_token(token_type)
+            }
+            '+' => {
+                let token_type = if self.advance_if_is('+') {
+                    TokenType::PlusPlus
+                } else if self.advance_if_is('=') {
+                    TokenType::PlusEqual
+                } else {
+                    TokenType::Plus
+                };
+                self.add_token(token_type)
+            }
             ';' => self.add_token(TokenType::Semicolon),
-            '*' => self.add_token(TokenType::Star),
+            '%' => self.add_token(TokenType::Percent),
+            '&' => self.add_token(TokenType::Ampersand),
+            '|' => self.add_token(TokenType::Pipe),
+            '^' => self.add_token(TokenType::Caret),
+            '*' => {
+                let token_type = if self.advance_if_is('*') {
+                    TokenType::StarStar
+                } else if self.advance_if_is('=') {
+                    TokenType::StarEqual
+                } else {
+                    TokenType::Star
+                };
+                self.add_token(token_type)
+            }
+            '~' => {
+                let token_type = if self.advance_if_is('/') {
+                    TokenType::TildeSlash
+                } else {
+                    TokenType::Tilde
+                };
+                self.add_token(token_type)
+            }
             '!' => if_equals_else(TokenType::BangEqual, TokenType::Bang),
-            '=' => if_equals_else(TokenType::EqualEqual, TokenType::Equal),
-            '<' => if_equals_else(TokenType::LessEqual, TokenType::Less),
-            '>' => if_equals_else(TokenType::GreaterEqual, TokenType::Greater),
+            '=' => {
+                let token_type = if self.advance_if_is('>') {
+                    TokenType::FatArrow
+                } else if self.advance_if_is('=') {
+                    TokenType::EqualEqual
+                } else {
+                    TokenType::Equal
+                };
+                self.add_token(token_type)
+            }
+            '<' => {
+                let token_type = if self.advance_if_is('<') {
+                    TokenType::LessLess
+                } else if self.advance_if_is('=') {
+                    TokenType::LessEqual
+                } else {
+                    TokenType::Less
+                };
+                self.add_token(token_type)
+            }
+            '>' => {
+                let token_type = if self.advance_if_is('>') {
+                    TokenType::GreaterGreater
+                } else if self.advance_if_is('=') {
+                    TokenType::GreaterEqual
+                } else {
+                    TokenType::Greater
+                };
+                self.add_token(token_type)
+            }
             '/' => {
                 match self.peek() {
                     '/' => {
@@ -92,6 +191,10 @@ impl Scanner {
                         self.advance();
                         self.advance();
                     }
+                    '=' => {
+                        self.advance();
+                        self.add_token(TokenType::SlashEqual)
+                    }
                     _ => self.add_token(TokenType::Slash),
                 }
             }
@@ -102,10 +205,11 @@ impl Scanner {
             '"' => self.string()?,
             '0'..='9' => self.number(),
             c if is_alpha(c) => self.identifier(),
+            '#' if is_alpha(self.peek()) => self.private_identifier(),
             _ => {
                 return Err(LoxError::Parsing {
                     line: self.line,
-                    whence: std::ascii::escape_default(c as u8).to_string(),
+                    whence: c.escape_debug().to_string(),
                     message: "Unexpected character".to_string(),
                 });
             }
@@ -115,8 +219,8 @@ impl Scanner {
     }
 
     fn advance(&mut self) -> char {
-        let next = self.source.as_bytes()[self.current] as char;
-        self.current += 1;
+        let next = self.peek();
+        self.current += next.len_utf8();
         next
     }
 
@@ -130,38 +234,43 @@ impl Scanner {
 
     fn add_token_with_literal(&mut self, token_type: TokenType, literal: crate::object::Literal) {
         let text = &self.source[self.start..self.current];
-        self.tokens
-            .push(Token::new(token_type, text, literal, self.line))
+        self.tokens.push(Token::new(token_type, text, literal, self.line))
     }
 
     fn advance_if_is(&mut self, expected: char) -> bool {
         if self.is_at_end() {
             return false;
         }
-        if self.source.as_bytes()[self.current] as char != expected {
+        if self.peek() != expected {
             return false;
         }
 
-        self.current += 1;
+        self.current += expected.len_utf8();
         true
     }
 
     fn peek(&self) -> char {
-        if self.is_at_end() {
-            return '\0';
-        }
-        self.source.as_bytes()[self.current] as char
+        self.source[self.current..].chars().next().unwrap_or('\0')
     }
 
     fn peek_next(&self) -> char {
-        if self.current + 1 >= self.source.len() {
-            return '\0';
-        }
-        self.source.as_bytes()[self.current + 1] as char
+        self.source[self.current..].chars().nth(1).unwrap_or('\0')
     }
 
+    /// Scans string contents up to the closing quote, or up to the next `${`. In the latter case an
+    /// `Interpolation` token holds the text so far, and scanning carries on with the embedded
+    /// expression's tokens until its closing `}` resumes the string
     fn string(&mut self) -> Result<()> {
+        let content_start = self.current;
         while self.peek() != '"' && !self.is_at_end() {
+            if self.peek() == '$' && self.peek_next() == '{' {
+                let val = self.source[content_start..self.current].to_string();
+                self.advance();
+                self.advance();
+                self.interpolations.push(0);
+                self.add_token_with_literal(TokenType::Interpolation, Literal::String(val));
+                return Ok(());
+            }
             if self.peek() == '\n' {
                 self.line += 1;
             }
@@ -179,14 +288,13 @@ impl Scanner {
         // The closing "
         self.advance();
 
-        let val = &self.source[self.start + 1..self.current - 1];
-        self.add_token_with_literal(
-            TokenType::String,
-            crate::object::Literal::String(val.to_string()),
-        );
+        let val = &self.source[content_start..self.current - 1];
+        self.add_token_with_literal(TokenType::String, crate::object::Literal::String(val.to_string()));
         Ok(())
     }
 
+    /// Numbers without a fractional part are integers, unless they're too big for one. Anything
+    /// else is a float
     fn number(&mut self) {
         while self.peek().is_ascii_digit() {
             self.advance();
@@ -196,19 +304,22 @@ impl Scanner {
         if self.peek() == '.' && self.peek_next().is_ascii_digit() {
             // Consume the '.'
             self.advance();
-        }
 
-        while self.peek().is_ascii_digit() {
-            self.advance();
+            while self.peek().is_ascii_digit() {
+                self.advance();
+            }
+
+            let as_float: f64 = self.source[self.start..self.current].parse::<f64>().expect("Better be a number");
+            self.add_token_with_literal(TokenType::Number, crate::object::Literal::Number(OrderedFloat(as_float)));
+            return;
         }
 
-        let as_float: f64 = self.source[self.start..self.current]
-            .parse::<f64>()
-            .expect("Better be a number");
-        self.add_token_with_literal(
-            TokenType::Number,
-            crate::object::Literal::Number(OrderedFloat(as_float)),
-        )
+        let text = &self.source[self.start..self.current];
+        let literal = match text.parse::<i64>() {
+            Ok(as_integer) => crate::object::Literal::Integer(as_integer),
+            Err(_) => crate::object::Literal::Number(OrderedFloat(text.parse::<f64>().expect("Better be a number"))),
+        };
+        self.add_token_with_literal(TokenType::Number, literal);
     }
 
     fn identifier(&mut self) {
@@ -224,11 +335,48 @@ impl Scanner {
 
         self.add_token(token_type);
     }
+
+    /// `#name`, which is never a keyword
+    fn private_identifier(&mut self) {
+        while is_alphanumeric(self.peek()) {
+            self.advance();
+        }
+        self.add_token(TokenType::PrivateIdentifier);
+    }
 }
+/// Identifiers follow Unicode's XID rules, plus a leading `_`
 fn is_alpha(c: char) -> bool {
-    c.is_alphabetic() || c == '_'
+    c.is_xid_start() || c == '_'
 }
 
 fn is_alphanumeric(c: char) -> bool {
-    c.is_alphanumeric() || c == '_'
+    c.is_xid_continue()
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn scans_multi_byte_source() {
+        let tokens = Scanner::new("var größe = \"日本語 ß\";".to_string()).scan_tokens().unwrap();
+        let scanned = tokens
+            .iter()
+            .map(|token| (token.typ.clone(), token.lexeme.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            scanned,
+            vec![
+                (TokenType::Var, "var"),
+                (TokenType::Identifier, "größe"),
+                (TokenType::Equal, "="),
+                (TokenType::String, "\"日本語 ß\""),
+                (TokenType::Semicolon, ";"),
+                (TokenType::Eof, ""),
+            ]
+        );
+        assert_eq!(tokens[3].literal, Literal::String("日本語 ß".into()));
+    }
 }
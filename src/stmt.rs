@@ -1,4 +1,8 @@
-use crate::{expr::Expr, token::Token};
+use crate::{
+    expr::{self, Expr},
+    object::Literal,
+    token::Token,
+};
 
 #[derive(Clone)]
 pub struct Expression {
@@ -19,6 +23,7 @@ impl Expression {
 
 #[derive(Clone)]
 pub struct Print {
+    pub keyword: Token,
     pub expression: Expr,
 }
 
@@ -29,8 +34,11 @@ impl std::fmt::Debug for Print {
 }
 
 impl Print {
-    pub fn stmt(value: Expr) -> Stmt {
-        Stmt::Print(Self { expression: value })
+    pub fn stmt(keyword: Token, value: Expr) -> Stmt {
+        Stmt::Print(Self {
+            keyword,
+            expression: value,
+        })
     }
 }
 
@@ -38,6 +46,8 @@ impl Print {
 pub struct Var {
     pub name: Token,
     pub initializer: Option<Expr>,
+    /// Declared with `const`, so it can't be reassigned
+    pub constant: bool,
 }
 // var captured = obj.captureThis("baz");
 
@@ -60,13 +70,26 @@ impl std::fmt::Debug for Var {
         } else {
             "nil".to_string()
         };
-        write!(f, "VarStmt(var {} = {} [Line: {}])", name, val, line)
+        let keyword = if self.constant { "const" } else { "var" };
+        write!(f, "VarStmt({} {} = {} [Line: {}])", keyword, name, val, line)
     }
 }
 
 impl Var {
     pub fn stmt(name: Token, initializer: Option<Expr>) -> Stmt {
-        Stmt::Var(Self { name, initializer })
+        Stmt::Var(Self {
+            name,
+            initializer,
+            constant: false,
+        })
+    }
+
+    pub fn constant(name: Token, initializer: Expr) -> Stmt {
+        Stmt::Var(Self {
+            name,
+            initializer: Some(initializer),
+            constant: true,
+        })
     }
 }
 
@@ -98,19 +121,60 @@ pub struct If {
 pub struct While {
     pub condition: Expr,
     pub body: Box<Stmt>,
+    /// Run after every iteration, including ones cut short by `continue`
+    pub increment: Option<Expr>,
+    pub label: Option<Token>,
 }
 
 impl std::fmt::Debug for While {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "While ({:?}) {{ {:?} }}", &self.condition, &self.body)
+        if let Some(label) = &self.label {
+            write!(f, "{}: ", label.lexeme)?;
+        }
+        write!(f, "While ({:?}) {{ {:?} }}", &self.condition, &self.body)?;
+        if let Some(increment) = &self.increment {
+            write!(f, " then {:?}", increment)?;
+        }
+        Ok(())
     }
 }
 
 impl While {
-    pub fn stmt(condition: Expr, body: Stmt) -> Stmt {
+    pub fn stmt(condition: Expr, body: Stmt, increment: Option<Expr>, label: Option<Token>) -> Stmt {
         Stmt::While(Self {
             condition,
             body: Box::new(body),
+            increment,
+            label,
+        })
+    }
+}
+
+/// `for (var name in iterable) body`, with a fresh `name` for every iteration
+#[derive(Clone)]
+pub struct ForIn {
+    pub name: Token,
+    pub iterable: Expr,
+    pub body: Box<Stmt>,
+    pub label: Option<Token>,
+}
+
+impl std::fmt::Debug for ForIn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(label) = &self.label {
+            write!(f, "{}: ", label.lexeme)?;
+        }
+        write!(f, "ForIn ({} in {:?}) {{ {:?} }}", self.name.lexeme, &self.iterable, &self.body)
+    }
+}
+
+impl ForIn {
+    pub fn stmt(name: Token, iterable: Expr, body: Stmt, label: Option<Token>) -> Stmt {
+        Stmt::ForIn(Self {
+            name,
+            iterable,
+            body: Box::new(body),
+            label,
         })
     }
 }
@@ -130,12 +194,32 @@ impl If {
 pub struct Function {
     pub name: Token,
     pub params: Vec<Token>,
+    /// A default value for each of `params`, evaluated at call time if that argument is left out
+    pub defaults: Vec<Option<Expr>>,
+    /// `...rest` collects any arguments past `params` into a list
+    pub rest: Option<Token>,
     pub body: Vec<Stmt>,
+    /// A method declared without a parameter list, which runs as soon as it's accessed
+    pub getter: bool,
+    /// Has a `yield` in its body, so calling it gives back a generator rather than running it
+    pub generator: bool,
 }
 
 impl Function {
+    pub fn new(name: Token, params: Vec<Token>, body: Vec<Stmt>) -> Self {
+        Self {
+            name,
+            defaults: vec![None; params.len()],
+            params,
+            rest: None,
+            generator: body.iter().any(Stmt::yields),
+            body,
+            getter: false,
+        }
+    }
+
     pub fn stmt(name: Token, params: Vec<Token>, body: Vec<Stmt>) -> Stmt {
-        Stmt::Function(Self { name, params, body })
+        Stmt::Function(Self::new(name, params, body))
     }
 }
 
@@ -162,15 +246,247 @@ impl Return {
     }
 }
 
+/// `yield value;` hands a value out of a generator, which picks up from here on its next `next()`
+#[derive(Clone)]
+pub struct Yield {
+    pub keyword: Token,
+    pub value: Option<Expr>,
+}
+
+impl std::fmt::Debug for Yield {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.value {
+            Some(value) => write!(f, "yield {:?};", value),
+            None => write!(f, "yield;"),
+        }
+    }
+}
+
+impl Yield {
+    pub fn stmt(keyword: Token, value: Option<Expr>) -> Stmt {
+        Stmt::Yield(Self { keyword, value })
+    }
+}
+
+#[derive(Clone)]
+pub struct Break {
+    pub keyword: Token,
+    pub label: Option<Token>,
+}
+
+impl std::fmt::Debug for Break {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.label {
+            Some(label) => write!(f, "break {};", label.lexeme),
+            None => write!(f, "break;"),
+        }
+    }
+}
+
+impl Break {
+    pub fn stmt(keyword: Token, label: Option<Token>) -> Stmt {
+        Stmt::Break(Self { keyword, label })
+    }
+}
+
+#[derive(Clone)]
+pub struct Continue {
+    pub keyword: Token,
+    pub label: Option<Token>,
+}
+
+impl std::fmt::Debug for Continue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.label {
+            Some(label) => write!(f, "continue {};", label.lexeme),
+            None => write!(f, "continue;"),
+        }
+    }
+}
+
+impl Continue {
+    pub fn stmt(keyword: Token, label: Option<Token>) -> Stmt {
+        Stmt::Continue(Self { keyword, label })
+    }
+}
+
+#[derive(Clone)]
+pub struct Import {
+    pub keyword: Token,
+    /// Path of the file to load, relative to the importing file
+    pub path: String,
+    /// What the module is bound as when no names are selected
+    pub name: String,
+    /// Names to bring into scope directly, instead of the module itself
+    pub names: Vec<Token>,
+}
+
+impl std::fmt::Debug for Import {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "import \"{}\"", self.path)?;
+        if !self.names.is_empty() {
+            let names = self.names.iter().map(|name| name.lexeme.as_str()).collect::<Vec<_>>();
+            write!(f, " ({})", names.join(", "))?;
+        }
+        write!(f, ";")
+    }
+}
+
+impl Import {
+    pub fn stmt(keyword: Token, path: String, name: String, names: Vec<Token>) -> Stmt {
+        Stmt::Import(Self {
+            keyword,
+            path,
+            name,
+            names,
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct Throw {
+    pub keyword: Token,
+    pub value: Expr,
+}
+
+impl std::fmt::Debug for Throw {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "throw {:?};", self.value)
+    }
+}
+
+impl Throw {
+    pub fn stmt(keyword: Token, value: Expr) -> Stmt {
+        Stmt::Throw(Self { keyword, value })
+    }
+}
+
+/// The `catch (name) { ... }` part of a `try` statement
+#[derive(Clone, Debug)]
+pub struct Catch {
+    pub name: Token,
+    pub body: Vec<Stmt>,
+}
+
+#[derive(Clone)]
+pub struct Try {
+    pub body: Vec<Stmt>,
+    pub catch: Option<Catch>,
+    /// Always runs, whether the body or catch block finished normally or not
+    pub finally: Option<Vec<Stmt>>,
+}
+
+impl std::fmt::Debug for Try {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "try {:?}", self.body)?;
+        if let Some(catch) = &self.catch {
+            write!(f, " catch ({}) {:?}", catch.name.lexeme, catch.body)?;
+        }
+        if let Some(finally) = &self.finally {
+            write!(f, " finally {:?}", finally)?;
+        }
+        Ok(())
+    }
+}
+
+impl Try {
+    pub fn stmt(body: Vec<Stmt>, catch: Option<Catch>, finally: Option<Vec<Stmt>>) -> Stmt {
+        Stmt::Try(Self { body, catch, finally })
+    }
+}
+
+/// What a `match` arm compares the value against
+#[derive(Clone)]
+pub enum Pattern {
+    /// `_` matches anything, without binding it
+    Wildcard,
+    /// A number, string, boolean or `nil`, compared with `==`
+    Literal(Literal),
+    /// `start..end` leaves out `end`, `start..=end` includes it
+    Range { start: Literal, end: Literal, inclusive: bool },
+    /// A name matches anything, and binds the value to it in the arm
+    Binding(Token),
+    /// `a | b`, which can't bind any names since not every alternative would
+    Alternatives(Vec<Pattern>),
+    /// `Point(x, y: 0)` matches instances of `Point` or its subclasses. `x` on its own binds the
+    /// field to a variable of the same name, `y: pattern` matches the field against a pattern
+    Class {
+        name: expr::Variable,
+        fields: Vec<(Token, Pattern)>,
+    },
+}
+
+impl std::fmt::Debug for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Pattern::Wildcard => write!(f, "_"),
+            Pattern::Literal(literal) => write!(f, "{literal:?}"),
+            Pattern::Range { start, end, inclusive } => {
+                write!(f, "{start}..{}{end}", if *inclusive { "=" } else { "" })
+            }
+            Pattern::Binding(name) => write!(f, "{}", name.lexeme),
+            Pattern::Alternatives(alternatives) => {
+                let alternatives = alternatives.iter().map(|p| format!("{p:?}")).collect::<Vec<_>>();
+                write!(f, "{}", alternatives.join(" | "))
+            }
+            Pattern::Class { name, fields } => {
+                let fields = fields
+                    .iter()
+                    .map(|(field, p)| format!("{}: {p:?}", field.lexeme))
+                    .collect::<Vec<_>>();
+                write!(f, "{}({})", name.name.lexeme, fields.join(", "))
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub body: Stmt,
+}
+
+/// Runs the first arm whose pattern matches the value
+#[derive(Clone)]
+pub struct Match {
+    pub keyword: Token,
+    pub value: Expr,
+    pub arms: Vec<MatchArm>,
+}
+
+impl std::fmt::Debug for Match {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "match ({:?}) {{ ", self.value)?;
+        for arm in self.arms.iter() {
+            write!(f, "{:?} => {:?}, ", arm.pattern, arm.body)?;
+        }
+        write!(f, "}}")
+    }
+}
+
+impl Match {
+    pub fn stmt(keyword: Token, value: Expr, arms: Vec<MatchArm>) -> Stmt {
+        Stmt::Match(Self { keyword, value, arms })
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Class {
     pub name: Token,
+    pub superclass: Option<expr::Variable>,
     pub methods: Vec<Function>,
+    /// Methods declared with a `class` prefix, called on the class itself
+    pub class_methods: Vec<Function>,
 }
 
 impl Class {
-    pub fn stmt(name: Token, methods: Vec<Function>) -> Stmt {
-        Stmt::Class(Self { name, methods })
+    pub fn stmt(name: Token, superclass: Option<expr::Variable>, methods: Vec<Function>, class_methods: Vec<Function>) -> Stmt {
+        Stmt::Class(Self {
+            name,
+            superclass,
+            methods,
+            class_methods,
+        })
     }
 }
 
@@ -182,8 +498,16 @@ pub enum Stmt {
     Var(Var),
     If(If),
     While(While),
+    ForIn(ForIn),
     Function(Function),
     Return(Return),
+    Yield(Yield),
+    Break(Break),
+    Continue(Continue),
+    Import(Import),
+    Match(Match),
+    Throw(Throw),
+    Try(Try),
     Class(Class),
 }
 
@@ -197,8 +521,46 @@ impl std::fmt::Debug for Stmt {
             Self::Print(stmt) => write!(f, "{:?}", stmt),
             Self::Var(stmt) => write!(f, "{:?}", stmt),
             Self::While(stmt) => write!(f, "{:?}", stmt),
+            Self::ForIn(stmt) => write!(f, "{:?}", stmt),
             Self::Return(stmt) => write!(f, "{:?}", stmt),
+            Self::Yield(stmt) => write!(f, "{:?}", stmt),
+            Self::Break(stmt) => write!(f, "{:?}", stmt),
+            Self::Continue(stmt) => write!(f, "{:?}", stmt),
+            Self::Import(stmt) => write!(f, "{:?}", stmt),
+            Self::Match(stmt) => write!(f, "{:?}", stmt),
+            Self::Throw(stmt) => write!(f, "{:?}", stmt),
+            Self::Try(stmt) => write!(f, "{:?}", stmt),
             Self::Class(stmt) => write!(f, "{:?}", stmt),
         }
     }
 }
+
+impl Stmt {
+    /// Whether there's a `yield` in this statement. Functions declared inside it don't count,
+    /// since they're generators (or not) in their own right
+    pub fn yields(&self) -> bool {
+        match self {
+            Stmt::Yield(_) => true,
+            Stmt::Block(block) => block.statements.iter().any(Stmt::yields),
+            Stmt::If(stmt) => stmt.then_branch.yields() || stmt.else_branch.as_ref().is_some_and(|branch| branch.yields()),
+            Stmt::While(stmt) => stmt.body.yields(),
+            Stmt::ForIn(stmt) => stmt.body.yields(),
+            Stmt::Match(stmt) => stmt.arms.iter().any(|arm| arm.body.yields()),
+            Stmt::Try(stmt) => {
+                stmt.body.iter().any(Stmt::yields)
+                    || stmt.catch.as_ref().is_some_and(|catch| catch.body.iter().any(Stmt::yields))
+                    || stmt.finally.as_ref().is_some_and(|finally| finally.iter().any(Stmt::yields))
+            }
+            Stmt::Expression(_)
+            | Stmt::Print(_)
+            | Stmt::Var(_)
+            | Stmt::Function(_)
+            | Stmt::Return(_)
+            | Stmt::Break(_)
+            | Stmt::Continue(_)
+            | Stmt::Import(_)
+            | Stmt::Throw(_)
+            | Stmt::Class(_) => false,
+        }
+    }
+}
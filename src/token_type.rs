@@ -5,6 +5,10 @@ pub enum TokenType {
     RightParen,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
+    Colon,
+    Question,
     Comma,
     Dot,
     Minus,
@@ -12,6 +16,12 @@ pub enum TokenType {
     Semicolon,
     Slash,
     Star,
+    Percent,
+    Ampersand,
+    Pipe,
+    Caret,
+    /// `~`, bitwise not
+    Tilde,
 
     // One or two character tokens
     Bang,
@@ -22,29 +32,62 @@ pub enum TokenType {
     GreaterEqual,
     Less,
     LessEqual,
+    /// `~/`, integer division
+    TildeSlash,
+    StarStar,
+    LessLess,
+    GreaterGreater,
+    PlusEqual,
+    MinusEqual,
+    StarEqual,
+    SlashEqual,
+    PlusPlus,
+    MinusMinus,
+    /// `=>`, between a pattern and its arm in a `match`
+    FatArrow,
+    /// `..`, an exclusive range in a pattern
+    DotDot,
+    /// `..=`, an inclusive range in a pattern
+    DotDotEqual,
+    DotDotDot,
 
     // Literals
     Identifier,
+    /// `#name`, a field or method only reachable through `this` inside its own class
+    PrivateIdentifier,
     String,
+    /// The part of a string literal before an embedded `${...}` expression
+    Interpolation,
     Number,
 
     // Keywords
     And,
+    Break,
+    Catch,
     Class,
+    Const,
+    Continue,
     Else,
     False,
+    Finally,
     Fun,
     For,
     If,
+    Import,
+    In,
+    Match,
     Nil,
     Or,
     Print,
     Return,
     Super,
     This,
+    Throw,
     True,
+    Try,
     Var,
     While,
+    Yield,
 
     Eof,
 }
@@ -53,22 +96,33 @@ impl TokenType {
     pub fn try_from_identifier(ident: &str) -> Option<TokenType> {
         match ident {
             "and" => Some(TokenType::And),
+            "break" => Some(TokenType::Break),
+            "catch" => Some(TokenType::Catch),
             "class" => Some(TokenType::Class),
+            "const" => Some(TokenType::Const),
+            "continue" => Some(TokenType::Continue),
             "else" => Some(TokenType::Else),
             "false" => Some(TokenType::False),
+            "finally" => Some(TokenType::Finally),
             "for" => Some(TokenType::For),
             "fun" => Some(TokenType::Fun),
 
             "if" => Some(TokenType::If),
+            "import" => Some(TokenType::Import),
+            "in" => Some(TokenType::In),
+            "match" => Some(TokenType::Match),
             "nil" => Some(TokenType::Nil),
             "or" => Some(TokenType::Or),
             "print" => Some(TokenType::Print),
             "return" => Some(TokenType::Return),
             "super" => Some(TokenType::Super),
             "this" => Some(TokenType::This),
+            "throw" => Some(TokenType::Throw),
             "true" => Some(TokenType::True),
+            "try" => Some(TokenType::Try),
             "var" => Some(TokenType::Var),
             "while" => Some(TokenType::While),
+            "yield" => Some(TokenType::Yield),
             _ => None,
         }
     }